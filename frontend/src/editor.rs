@@ -2,7 +2,11 @@ use std::io::{BufReader, Cursor};
 use yew::{html, Component, Context, Html, events::Event};
 use web_sys::{EventTarget, HtmlTextAreaElement};
 use wasm_bindgen::JsCast;
-use interpreter::interpret;
+use interpreter::{interpret_with_options, Options};
+
+/// Programs are stopped after this many steps, so that an endless loop does
+/// not freeze the page.
+const MAX_STEPS: u64 = 10_000_000;
 
 pub enum Msg {
     Execute,
@@ -31,8 +35,15 @@ impl Component for Editor {
                 let mut input = Cursor::new(vec![]);
                 let mut output_stream: Vec<u8> = Vec::new();
                 let mut out = Cursor::new(&mut output_stream);
-                interpret(&mut input, &mut out, &self.code).expect("Wrong code!");
+                let options = Options {
+                    max_steps: Some(MAX_STEPS),
+                    ..Options::default()
+                };
+                let result = interpret_with_options(&mut input, &mut out, &self.code, &options);
                 self.result = String::from_utf8(output_stream).unwrap();
+                if let Err(e) = result {
+                    self.result.push_str(&e.to_string());
+                }
             }
             Msg::EditorChange(code) => {
                 self.code = code;
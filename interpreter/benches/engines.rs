@@ -0,0 +1,55 @@
+use std::{fs::read_to_string, io::Cursor};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use interpreter::{interpret_with_options, Engine, Options};
+
+const NESTED_LOOPS: &str = "s <- 0
+pentru i <- 1, 300 executa
+  pentru j <- 1, 300 executa
+    s <- (s + i * j) % 1000
+scrie s";
+
+fn run(engine: Engine, program: &str, input: &str) {
+    let mut reader = Cursor::new(input);
+    let mut writer = Cursor::new(Vec::new());
+    let options = Options {
+        engine,
+        ..Options::default()
+    };
+    interpret_with_options(&mut reader, &mut writer, program, &options).unwrap();
+}
+
+/// Runs the baseline that looks variables up by name, with `--features by-name`.
+#[cfg(feature = "by-name")]
+fn run_by_name(program: &str, input: &str) {
+    let mut reader = Cursor::new(input);
+    let mut writer = Cursor::new(Vec::new());
+    interpreter::interpret_by_name(&mut reader, &mut writer, program).unwrap();
+}
+
+fn engines(c: &mut Criterion) {
+    let fibonacci = read_to_string("tests/resources/fibonacci.pseudo").unwrap();
+    let samples = [
+        ("fibonacci", fibonacci.as_str(), "40"),
+        ("nested loops", NESTED_LOOPS, ""),
+    ];
+    let mut group = c.benchmark_group("engines");
+    for (name, program, input) in samples {
+        #[cfg(feature = "by-name")]
+        group.bench_with_input(
+            BenchmarkId::new("TreeWalkerByName", name),
+            &program,
+            |b, program| b.iter(|| run_by_name(program, input)),
+        );
+        for engine in [Engine::TreeWalker, Engine::Bytecode] {
+            let id = BenchmarkId::new(format!("{:?}", engine), name);
+            group.bench_with_input(id, &program, |b, program| {
+                b.iter(|| run(engine, program, input))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, engines);
+criterion_main!(benches);
@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+
+use super::resolve::{Resolved, Slot};
+use super::{unescape, ArithmeticOperation, Value};
+use crate::frontend::expression::{Expression, LValue, OrderType};
+use crate::frontend::instruction::{Instruction, Span, Statement, Subprogram, WhileType, Writable};
+
+/// The operations of the virtual machine. They work on a stack of values and
+/// on the variables of the running subprogram, which are numbered slots.
+#[derive(Clone, Debug)]
+pub enum Op<'a> {
+    /// Pushes one of the constants of the program.
+    Push(usize),
+    Load(usize),
+    Store(usize),
+    Pop,
+    /// Checks the index on top of the stack against the array in the slot and
+    /// moves it to the positions used by the next element access.
+    Position(usize),
+    /// Pushes the element of the slot at the last `count` positions.
+    LoadElement(usize, usize),
+    /// Stores the value on top of the stack at the last `count` positions.
+    StoreElement(usize, usize),
+    /// Pushes the next word of the input.
+    Read,
+    Write,
+    Newline,
+    Negate,
+    IntegerPart,
+    Absolute,
+    Not,
+    /// Replaces the value on top of the stack with 1 if it is true, else 0.
+    Truth,
+    Arithmetic(ArithmeticOperation),
+    Order(OrderType),
+    Jump(usize),
+    JumpIfFalse(usize),
+    JumpIfTrue(usize),
+    CallBuiltIn(&'a str, usize),
+    /// Calls a subprogram of the program. When `result` is set, the value it
+    /// returns is pushed and a procedure is an error.
+    Call {
+        chunk: usize,
+        arguments: usize,
+        result: bool,
+    },
+    /// Returns from the subprogram, with the value on top of the stack if set.
+    Return(bool),
+    /// Stores in `direction` whether the step of a `pentru` loop is ascending.
+    ForDirection {
+        step: usize,
+        direction: usize,
+    },
+    /// Pushes the loop variable, which must be a number.
+    ForCurrent(usize),
+    /// Jumps to `exit` when the variable under the end value is past it.
+    ForTest {
+        direction: usize,
+        exit: usize,
+    },
+    ForStep {
+        variable: usize,
+        step: usize,
+    },
+    /// Checks that the step on top of the stack did not change.
+    CheckStep(usize),
+    /// Counts an instruction or a check of a loop condition towards the
+    /// limits of the program.
+    Tick,
+}
+
+/// The code of the main program or of one subprogram.
+pub struct Chunk<'a> {
+    pub name: &'a str,
+    pub parameters: usize,
+    pub code: Vec<Op<'a>>,
+    /// The instruction each operation was compiled from, to locate errors.
+    pub spans: Vec<Span<'a>>,
+    /// The variable names, by slot. The parameters come first, and the hidden
+    /// slots of `pentru` loops, which follow the variables, have empty names.
+    pub slots: Vec<&'a str>,
+}
+
+impl<'a> Chunk<'a> {
+    fn new(name: &'a str, parameters: usize, variables: &[&'a str]) -> Self {
+        Chunk {
+            name,
+            parameters,
+            code: Vec::new(),
+            spans: Vec::new(),
+            slots: variables.to_vec(),
+        }
+    }
+}
+
+pub struct Program<'a> {
+    pub constants: Vec<Value>,
+    /// The main program comes first, followed by the subprograms.
+    pub chunks: Vec<Chunk<'a>>,
+}
+
+/// Compiles a program to bytecode. Calls are resolved the way
+/// `execute_program` resolves them: to the last subprogram with that name, or
+/// otherwise to a built-in function.
+pub fn compile<'a>(program: &'a Resolved<'a>) -> Program<'a> {
+    let mut subprograms: Vec<&'a Subprogram<'a, Slot<'a>>> = Vec::new();
+    let mut functions = HashMap::new();
+    for statement in &program.program {
+        if let Instruction::Subprogram(subprogram) = &statement.instruction {
+            match functions.get(subprogram.name) {
+                Some(&chunk) => subprograms[chunk - 1] = subprogram,
+                None => {
+                    subprograms.push(subprogram);
+                    functions.insert(subprogram.name, subprograms.len());
+                }
+            }
+        }
+    }
+
+    let mut compiler = Compiler {
+        constants: Vec::new(),
+        functions,
+        chunk: Chunk::new("", 0, &[]),
+        span: Span::default(),
+    };
+    let main = Chunk::new("", 0, &program.variables);
+    let mut chunks = vec![compiler.chunk(main, &program.program)];
+    for subprogram in subprograms {
+        let variables = &program.subprograms[subprogram.name];
+        let chunk = Chunk::new(subprogram.name, subprogram.parameters.len(), variables);
+        chunks.push(compiler.chunk(chunk, &subprogram.block));
+    }
+    Program {
+        constants: compiler.constants,
+        chunks,
+    }
+}
+
+struct Compiler<'a> {
+    constants: Vec<Value>,
+    functions: HashMap<&'a str, usize>,
+    chunk: Chunk<'a>,
+    /// The instruction being compiled.
+    span: Span<'a>,
+}
+
+impl<'a> Compiler<'a> {
+    fn chunk(&mut self, chunk: Chunk<'a>, block: &'a [Statement<'a, Slot<'a>>]) -> Chunk<'a> {
+        self.chunk = chunk;
+        self.block(block);
+        self.emit(Op::Return(false));
+        std::mem::replace(&mut self.chunk, Chunk::new("", 0, &[]))
+    }
+
+    fn emit(&mut self, op: Op<'a>) -> usize {
+        self.chunk.code.push(op);
+        self.chunk.spans.push(self.span);
+        self.chunk.code.len() - 1
+    }
+
+    /// Points the jump at `at` to the next operation.
+    fn patch(&mut self, at: usize) {
+        let next = self.chunk.code.len();
+        match &mut self.chunk.code[at] {
+            Op::Jump(target)
+            | Op::JumpIfFalse(target)
+            | Op::JumpIfTrue(target)
+            | Op::ForTest { exit: target, .. } => *target = next,
+            op => unreachable!("{:?} is not a jump", op),
+        }
+    }
+
+    fn constant(&mut self, value: Value) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    fn hidden_slot(&mut self) -> usize {
+        self.chunk.slots.push("");
+        self.chunk.slots.len() - 1
+    }
+
+    fn block(&mut self, block: &'a [Statement<'a, Slot<'a>>]) {
+        for statement in block {
+            let parent = std::mem::replace(&mut self.span, statement.span);
+            self.emit(Op::Tick);
+            self.instruction(&statement.instruction);
+            self.span = parent;
+        }
+    }
+
+    fn instruction(&mut self, instruction: &'a Instruction<'a, Slot<'a>>) {
+        match instruction {
+            Instruction::Read(targets) => {
+                for target in targets {
+                    self.emit(Op::Read);
+                    self.store(target);
+                }
+            }
+            Instruction::Assignment(target, expr) => {
+                self.expression(expr);
+                self.store(target);
+            }
+            Instruction::Write(writables, newline) => {
+                for writable in writables {
+                    match writable {
+                        Writable::Expression(expr) => self.expression(expr),
+                        Writable::String(text) => {
+                            let text = self.constant(Value::String(unescape(text)));
+                            self.emit(Op::Push(text));
+                        }
+                    }
+                    self.emit(Op::Write);
+                }
+                if *newline {
+                    self.emit(Op::Newline);
+                }
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                self.expression(cond);
+                let to_else = self.emit(Op::JumpIfFalse(0));
+                self.block(if_block);
+                match else_block {
+                    Some(else_block) => {
+                        let to_end = self.emit(Op::Jump(0));
+                        self.patch(to_else);
+                        self.block(else_block);
+                        self.patch(to_end);
+                    }
+                    None => self.patch(to_else),
+                }
+            }
+            Instruction::While(WhileType::While, cond, block) => {
+                let start = self.emit(Op::Tick);
+                self.expression(cond);
+                let to_end = self.emit(Op::JumpIfFalse(0));
+                self.block(block);
+                self.emit(Op::Jump(start));
+                self.patch(to_end);
+            }
+            Instruction::While(while_type, cond, block) => {
+                let start = self.chunk.code.len();
+                self.block(block);
+                self.emit(Op::Tick);
+                self.expression(cond);
+                if *while_type == WhileType::Repeat {
+                    self.emit(Op::JumpIfFalse(start));
+                } else {
+                    self.emit(Op::JumpIfTrue(start));
+                }
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => {
+                let variable = variable.index;
+                let step_slot = self.hidden_slot();
+                let direction = self.hidden_slot();
+                self.expression(start_expr);
+                self.emit(Op::Store(variable));
+                self.expression(step);
+                self.emit(Op::Store(step_slot));
+                self.emit(Op::ForDirection {
+                    step: step_slot,
+                    direction,
+                });
+                let start = self.emit(Op::Tick);
+                self.emit(Op::ForCurrent(variable));
+                self.expression(end_expr);
+                let to_end = self.emit(Op::ForTest { direction, exit: 0 });
+                self.block(block);
+                self.emit(Op::ForStep {
+                    variable,
+                    step: step_slot,
+                });
+                // A constant step cannot change, so it is not checked again.
+                if !matches!(step, Expression::Constant(_) | Expression::RealConstant(_)) {
+                    self.emit(Op::Load(direction));
+                    let to_next = self.emit(Op::JumpIfFalse(0));
+                    self.expression(step);
+                    self.emit(Op::CheckStep(step_slot));
+                    self.patch(to_next);
+                }
+                self.emit(Op::Jump(start));
+                self.patch(to_end);
+            }
+            Instruction::Call(name, arguments) => {
+                if !self.call(name, arguments, false) {
+                    self.emit(Op::Pop);
+                }
+            }
+            Instruction::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.expression(expr);
+                }
+                self.emit(Op::Return(expr.is_some()));
+            }
+            Instruction::Subprogram(_) => {}
+        }
+    }
+
+    /// Stores the value on top of the stack into `target`.
+    fn store(&mut self, target: &'a LValue<'a, Slot<'a>>) {
+        match target {
+            LValue::Variable(var) => {
+                self.emit(Op::Store(var.index));
+            }
+            LValue::Index(var, indices) => {
+                let slot = self.positions(*var, indices);
+                self.emit(Op::StoreElement(slot, indices.len()));
+            }
+        }
+    }
+
+    fn positions(&mut self, var: Slot<'a>, indices: &'a [Expression<'a, Slot<'a>>]) -> usize {
+        for index in indices {
+            self.expression(index);
+            self.emit(Op::Position(var.index));
+        }
+        var.index
+    }
+
+    /// Compiles a call, returning whether the called subprogram is one of the
+    /// program, whose result is only pushed when `result` is set.
+    fn call(
+        &mut self,
+        name: &'a str,
+        arguments: &'a [Expression<'a, Slot<'a>>],
+        result: bool,
+    ) -> bool {
+        for argument in arguments {
+            self.expression(argument);
+        }
+        match self.functions.get(name) {
+            Some(&chunk) => {
+                self.emit(Op::Call {
+                    chunk,
+                    arguments: arguments.len(),
+                    result,
+                });
+                true
+            }
+            None => {
+                self.emit(Op::CallBuiltIn(name, arguments.len()));
+                false
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &'a Expression<'a, Slot<'a>>) {
+        match expr {
+            &Expression::Constant(x) => {
+                let constant = self.constant(Value::Integer(x));
+                self.emit(Op::Push(constant));
+            }
+            &Expression::RealConstant(x) => {
+                let constant = self.constant(Value::Real(x));
+                self.emit(Op::Push(constant));
+            }
+            &Expression::StringConstant(text) => {
+                let constant = self.constant(Value::String(unescape(text)));
+                self.emit(Op::Push(constant));
+            }
+            &Expression::Variable(var) => {
+                self.emit(Op::Load(var.index));
+            }
+            &Expression::Index(var, ref indices) => {
+                let slot = self.positions(var, indices);
+                self.emit(Op::LoadElement(slot, indices.len()));
+            }
+            Expression::Minus(expr) => self.unary(expr, Op::Negate),
+            Expression::IntegerPart(expr) => self.unary(expr, Op::IntegerPart),
+            Expression::Absolute(expr) => self.unary(expr, Op::Absolute),
+            Expression::Not(expr) => self.unary(expr, Op::Not),
+            Expression::Addition(expr1, expr2) => {
+                self.binary(expr1, expr2, Op::Arithmetic(ArithmeticOperation::Addition))
+            }
+            Expression::Subtraction(expr1, expr2) => self.binary(
+                expr1,
+                expr2,
+                Op::Arithmetic(ArithmeticOperation::Subtraction),
+            ),
+            Expression::Multiplication(expr1, expr2) => self.binary(
+                expr1,
+                expr2,
+                Op::Arithmetic(ArithmeticOperation::Multiplication),
+            ),
+            Expression::Division(expr1, expr2) => {
+                self.binary(expr1, expr2, Op::Arithmetic(ArithmeticOperation::Division))
+            }
+            Expression::Reminder(expr1, expr2) => {
+                self.binary(expr1, expr2, Op::Arithmetic(ArithmeticOperation::Reminder))
+            }
+            Expression::Order(order_type, expr1, expr2) => {
+                self.binary(expr1, expr2, Op::Order(order_type.clone()))
+            }
+            // The second operand is skipped when the first decides the result.
+            Expression::And(expr1, expr2) => self.short_circuit(expr1, expr2, false),
+            Expression::Or(expr1, expr2) => self.short_circuit(expr1, expr2, true),
+            Expression::Call(name, arguments) => {
+                self.call(name, arguments, true);
+            }
+        }
+    }
+
+    fn unary(&mut self, expr: &'a Expression<'a, Slot<'a>>, op: Op<'a>) {
+        self.expression(expr);
+        self.emit(op);
+    }
+
+    fn binary(
+        &mut self,
+        expr1: &'a Expression<'a, Slot<'a>>,
+        expr2: &'a Expression<'a, Slot<'a>>,
+        op: Op<'a>,
+    ) {
+        self.expression(expr1);
+        self.expression(expr2);
+        self.emit(op);
+    }
+
+    fn short_circuit(
+        &mut self,
+        expr1: &'a Expression<'a, Slot<'a>>,
+        expr2: &'a Expression<'a, Slot<'a>>,
+        or: bool,
+    ) {
+        self.expression(expr1);
+        let to_decided = if or {
+            self.emit(Op::JumpIfTrue(0))
+        } else {
+            self.emit(Op::JumpIfFalse(0))
+        };
+        self.expression(expr2);
+        self.emit(Op::Truth);
+        let to_end = self.emit(Op::Jump(0));
+        self.patch(to_decided);
+        let decided = self.constant(Value::from(or));
+        self.emit(Op::Push(decided));
+        self.patch(to_end);
+    }
+}
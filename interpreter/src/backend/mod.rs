@@ -0,0 +1,1076 @@
+mod bytecode;
+mod resolve;
+mod vm;
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::frontend::expression::{Expression, LValue, OrderType};
+use crate::frontend::instruction::{Instruction, Span, Statement, Subprogram, WhileType, Writable};
+pub use resolve::resolve;
+use resolve::{Resolved, Slot};
+
+/// Arrays grow as their elements are assigned, up to this many elements.
+const MAX_ARRAY_LENGTH: usize = 1 << 20;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Value {
+    Integer(i32),
+    Real(f64),
+    /// Characters are strings of length one.
+    String(String),
+    /// Elements that were never assigned are `None`.
+    Array(Vec<Option<Value>>),
+}
+
+impl Value {
+    /// Input that starts like a number must be a valid number, anything else
+    /// is read as text. Digits alone are an integer, which must fit.
+    fn parse(input: &str) -> Result<Value, ExecutionErrorKind> {
+        if let Ok(value) = input.parse() {
+            return Ok(Value::Integer(value));
+        }
+        let unsigned = input.strip_prefix(['-', '+']).unwrap_or(input);
+        if !unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return Ok(Value::String(input.to_string()));
+        }
+        if unsigned.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ExecutionErrorKind::OverflowError);
+        }
+        match input.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Value::Real(value)),
+            _ => Err(ExecutionErrorKind::NumberParsingError(input.to_string())),
+        }
+    }
+
+    fn as_real(&self) -> Result<f64, ExecutionErrorKind> {
+        match *self {
+            Value::Integer(x) => Ok(x as f64),
+            Value::Real(x) => Ok(x),
+            _ => Err(self.number_expected()),
+        }
+    }
+
+    fn is_true(&self) -> Result<bool, ExecutionErrorKind> {
+        match *self {
+            Value::Integer(x) => Ok(x != 0),
+            Value::Real(x) => Ok(x != 0.0),
+            _ => Err(self.number_expected()),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Integer(_) => "un numar intreg",
+            Value::Real(_) => "un numar real",
+            Value::String(_) => "un text",
+            Value::Array(_) => "un tablou",
+        }
+    }
+
+    fn number_expected(&self) -> ExecutionErrorKind {
+        ExecutionErrorKind::TypeError("un numar", self.type_name())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(cond: bool) -> Self {
+        Value::Integer(cond as i32)
+    }
+}
+
+impl fmt::Display for Value {
+    /// Reals are printed with at most six decimals and without trailing zeros,
+    /// so `7 / 2.0` is written as `3.5` and `1 / 3.0` as `0.333333`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(x) => write!(f, "{}", x),
+            Value::Real(x) => {
+                let formatted = format!("{:.6}", x);
+                let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+                if formatted == "-0" {
+                    write!(f, "0")
+                } else {
+                    write!(f, "{}", formatted)
+                }
+            }
+            Value::String(text) => write!(f, "{}", text),
+            Value::Array(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " ")?;
+                    }
+                    match element {
+                        Some(element) => write!(f, "{}", element)?,
+                        None => write!(f, "?")?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Subprograms may call each other at most this deep, so that a recursion
+/// without a stopping condition is reported instead of crashing the interpreter.
+const MAX_CALL_DEPTH: usize = 1000;
+
+/// A call made with less native stack left than this runs on a new segment of
+/// `STACK_SEGMENT` bytes, whatever the stack of the thread running the program.
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT: usize = 4 * 1024 * 1024;
+
+/// Reading the clock is slow compared to a step, so the deadline is only
+/// checked once every this many steps.
+const STEPS_BETWEEN_CLOCK_CHECKS: u64 = 1024;
+
+/// Stops a running program from another thread, or from the interface that
+/// started it. Clones of a token share its state, and the program stops at its
+/// next step once any of them is cancelled.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+pub struct ExecutionContext<'a> {
+    pub program: &'a str,
+    /// The variables of the subprogram being executed, or of the main program,
+    /// by slot. Those that were not assigned yet are `None`.
+    pub variables: Vec<Option<Value>>,
+    /// The variables of the callers, saved while a subprogram is executed.
+    pub call_stack: Vec<Vec<Option<Value>>>,
+    /// The subprograms of the program, with the names of their variables.
+    pub subprograms: HashMap<&'a str, (&'a Subprogram<'a, Slot<'a>>, &'a [&'a str])>,
+    /// When set, the tree walker finds the slot of each variable by its name
+    /// in this map, made for the subprogram being executed or the main
+    /// program. It is only a baseline, to measure what resolving the slots
+    /// before the program runs saves.
+    #[cfg(feature = "by-name")]
+    pub names: Option<HashMap<&'a str, usize>>,
+    pub input: &'a mut dyn BufRead,
+    /// Words of the current input line that were not read yet.
+    pub tokens: VecDeque<String>,
+    pub output: &'a mut dyn Write,
+    /// How many steps were executed. Each instruction is a step, and so is
+    /// each check of the condition of a loop.
+    pub steps: u64,
+    /// The program is stopped after this many steps, if set.
+    pub max_steps: Option<u64>,
+    /// The program is stopped once this moment passes, if set.
+    pub deadline: Option<Instant>,
+    /// The program is stopped once this token is cancelled, if set.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl<'a> ExecutionContext<'a> {
+    pub fn new(program: &'a str, input: &'a mut dyn BufRead, output: &'a mut dyn Write) -> Self {
+        ExecutionContext {
+            program,
+            variables: Vec::new(),
+            call_stack: Vec::new(),
+            subprograms: HashMap::new(),
+            #[cfg(feature = "by-name")]
+            names: None,
+            input,
+            tokens: VecDeque::new(),
+            output,
+            steps: 0,
+            max_steps: None,
+            deadline: None,
+            cancellation: None,
+        }
+    }
+
+    /// Counts a step, failing if the program ran out of steps or of time, or
+    /// if it was cancelled.
+    fn tick(&mut self) -> Result<(), ExecutionErrorKind> {
+        if self.max_steps == Some(self.steps) {
+            return Err(ExecutionErrorKind::StepLimitExceeded(self.steps));
+        }
+        if let Some(cancellation) = &self.cancellation {
+            if cancellation.is_cancelled() {
+                return Err(ExecutionErrorKind::Cancelled);
+            }
+        }
+        self.steps += 1;
+        match self.deadline {
+            Some(deadline)
+                if self.steps.is_multiple_of(STEPS_BETWEEN_CLOCK_CHECKS)
+                    && Instant::now() >= deadline =>
+            {
+                Err(ExecutionErrorKind::TimeLimitExceeded(self.steps))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Where a variable is kept among those of the subprogram being executed.
+    fn index(&self, slot: Slot) -> usize {
+        #[cfg(feature = "by-name")]
+        if let Some(names) = &self.names {
+            return names[slot.name];
+        }
+        slot.index
+    }
+
+    fn variable(&self, slot: Slot) -> Result<&Value, ExecutionErrorKind> {
+        self.variables[self.index(slot)]
+            .as_ref()
+            .ok_or_else(|| ExecutionErrorKind::VariableNotDefinedError(slot.name.to_string()))
+    }
+
+    /// Reads the next whitespace-separated word of the input, moving on to
+    /// the following lines when the current one is used up.
+    fn read_token(&mut self) -> Result<String, ExecutionErrorKind> {
+        loop {
+            if let Some(token) = self.tokens.pop_front() {
+                return Ok(token);
+            }
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) => return Err(ExecutionErrorKind::EndOfInputError),
+                Ok(_) => self
+                    .tokens
+                    .extend(line.split_whitespace().map(str::to_string)),
+                Err(_) => return Err(ExecutionErrorKind::ReadingError),
+            }
+        }
+    }
+}
+
+/// What happens after an instruction: either the next one is executed, or the
+/// current subprogram returns, with a value if it is a function.
+#[derive(PartialEq, Debug)]
+enum Flow {
+    Next,
+    Return(Option<Value>),
+}
+
+/// Subprograms are defined before the program starts, so they can be called
+/// from lines above their definition. A `returneaza` outside of any subprogram
+/// ends the program.
+pub fn execute_program<'a>(
+    program: &'a Resolved<'a>,
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<(), ExecutionError> {
+    for statement in &program.program {
+        if let Instruction::Subprogram(subprogram) = &statement.instruction {
+            let variables = program.subprograms[subprogram.name].as_slice();
+            execution_context
+                .subprograms
+                .insert(subprogram.name, (subprogram, variables));
+        }
+    }
+    execution_context.variables = vec![None; program.variables.len()];
+    execute_block(&program.program, execution_context)?;
+    Ok(())
+}
+
+/// Runs the program like `execute_program`, but looks each variable up by its
+/// name, the way the tree walker did before the variables were resolved.
+#[cfg(feature = "by-name")]
+pub fn execute_by_name<'a>(
+    program: &'a Resolved<'a>,
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<(), ExecutionError> {
+    execution_context.names = Some(names(&program.variables));
+    execute_program(program, execution_context)
+}
+
+#[cfg(feature = "by-name")]
+fn names<'a>(variables: &[&'a str]) -> HashMap<&'a str, usize> {
+    variables
+        .iter()
+        .enumerate()
+        .map(|(index, &name)| (name, index))
+        .collect()
+}
+
+/// Runs the program like `execute_program`, but compiles it to bytecode first,
+/// which is faster for programs with loops or recursion.
+pub fn execute_compiled<'a>(
+    program: &'a Resolved<'a>,
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<(), ExecutionError> {
+    vm::run(&bytecode::compile(program), execution_context)
+}
+
+fn execute_block<'a>(
+    block: &'a [Statement<'a, Slot<'a>>],
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<Flow, ExecutionError> {
+    for statement in block {
+        let flow = statement
+            .instruction
+            .execute(execution_context)
+            .map_err(|e| e.located(statement.span, execution_context.program))?;
+        if flow != Flow::Next {
+            return Ok(flow);
+        }
+    }
+    Ok(Flow::Next)
+}
+
+impl<'a> Instruction<'a, Slot<'a>> {
+    fn execute(
+        &'a self,
+        execution_context: &mut ExecutionContext<'a>,
+    ) -> Result<Flow, ExecutionError> {
+        execution_context.tick()?;
+        match self {
+            Instruction::Read(vars) => {
+                for var in vars.iter() {
+                    let token = execution_context.read_token()?;
+                    let value = Value::parse(&token)?;
+                    store(var, value, execution_context)?;
+                }
+                Ok(Flow::Next)
+            }
+            Instruction::Assignment(target, expr) => {
+                let val = expr.evaluate(execution_context)?;
+                store(target, val, execution_context)?;
+                Ok(Flow::Next)
+            }
+            Instruction::Write(writables, newline) => {
+                for writable in writables {
+                    match writable {
+                        Writable::Expression(expr) => {
+                            let value = expr.evaluate(execution_context)?;
+                            if let Value::Array(_) = value {
+                                return Err(value.number_expected().into());
+                            }
+                            execution_context
+                                .output
+                                .write_all(value.to_string().as_bytes())
+                                .unwrap();
+                        }
+                        Writable::String(str) => execution_context
+                            .output
+                            .write_all(unescape(str).as_bytes())
+                            .unwrap(),
+                    }
+                }
+                if *newline {
+                    execution_context.output.write_all(b"\n").unwrap();
+                }
+                Ok(Flow::Next)
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                let block = if cond.evaluate(execution_context)?.is_true()? {
+                    Some(if_block)
+                } else {
+                    else_block.as_ref()
+                };
+                match block {
+                    Some(block) => execute_block(block, execution_context),
+                    None => Ok(Flow::Next),
+                }
+            }
+            Instruction::While(while_type, cond, block) => {
+                let repeat_while_true = *while_type != WhileType::Repeat;
+                if *while_type == WhileType::While {
+                    execution_context.tick()?;
+                    if !cond.evaluate(execution_context)?.is_true()? {
+                        return Ok(Flow::Next);
+                    }
+                }
+                loop {
+                    let flow = execute_block(block, execution_context)?;
+                    if flow != Flow::Next {
+                        return Ok(flow);
+                    }
+                    execution_context.tick()?;
+                    if cond.evaluate(execution_context)?.is_true()? != repeat_while_true {
+                        return Ok(Flow::Next);
+                    }
+                }
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => {
+                let initial = start_expr.evaluate(execution_context)?;
+                let index = execution_context.index(*variable);
+                execution_context.variables[index] = Some(initial);
+                let step_value = step.evaluate(execution_context)?;
+                let ascending = step_value.as_real()? >= 0.0;
+                loop {
+                    execution_context.tick()?;
+                    let current = execution_context.variable(*variable)?.as_real()?;
+                    let end = end_expr.evaluate(execution_context)?.as_real()?;
+                    let in_range = if ascending {
+                        current <= end
+                    } else {
+                        current >= end
+                    };
+                    if !in_range {
+                        break;
+                    }
+                    let flow = execute_block(block, execution_context)?;
+                    if flow != Flow::Next {
+                        return Ok(flow);
+                    }
+                    let current = execution_context.variable(*variable)?.clone();
+                    let next =
+                        arithmetic(ArithmeticOperation::Addition, current, step_value.clone())?;
+                    let index = execution_context.index(*variable);
+                    execution_context.variables[index] = Some(next);
+                    if ascending && step.evaluate(execution_context)? != step_value {
+                        return Err(ExecutionErrorKind::VariableStepInLoop.into());
+                    }
+                }
+                Ok(Flow::Next)
+            }
+            Instruction::Call(name, arguments) => {
+                call(name, arguments, execution_context)?;
+                Ok(Flow::Next)
+            }
+            Instruction::Return(expr) => {
+                let value = match expr {
+                    Some(expr) => Some(expr.evaluate(execution_context)?),
+                    None => None,
+                };
+                Ok(Flow::Return(value))
+            }
+            Instruction::Subprogram(_) => Ok(Flow::Next),
+        }
+    }
+}
+
+/// Calls a subprogram of the program or, if there is none with this name, a
+/// built-in function. Procedures have no result.
+fn call<'a>(
+    name: &str,
+    arguments: &[Expression<'a, Slot<'a>>],
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<Option<Value>, ExecutionError> {
+    let mut values = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        values.push(argument.evaluate(execution_context)?);
+    }
+    let (subprogram, variables) = match execution_context.subprograms.get(name) {
+        Some(&subprogram) => subprogram,
+        None => return call_built_in(name, &values).map(Some),
+    };
+    if values.len() != subprogram.parameters.len() {
+        return Err(ExecutionErrorKind::ArgumentCountError(
+            name.to_string(),
+            subprogram.parameters.len(),
+            values.len(),
+        )
+        .into());
+    }
+    if execution_context.call_stack.len() >= MAX_CALL_DEPTH {
+        return Err(ExecutionErrorKind::CallDepthError(MAX_CALL_DEPTH).into());
+    }
+    let mut locals = vec![None; variables.len()];
+    for (local, value) in locals.iter_mut().zip(values) {
+        *local = Some(value);
+    }
+    let caller = std::mem::replace(&mut execution_context.variables, locals);
+    execution_context.call_stack.push(caller);
+    #[cfg(feature = "by-name")]
+    let caller_names = execution_context
+        .names
+        .as_mut()
+        .map(|caller_names| std::mem::replace(caller_names, names(variables)));
+    // Each call nests the walk of a block on the native stack, which grows as
+    // needed so that the call depth is limited only by `MAX_CALL_DEPTH`.
+    let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || {
+        execute_block(&subprogram.block, execution_context)
+    });
+    execution_context.variables = execution_context.call_stack.pop().unwrap();
+    #[cfg(feature = "by-name")]
+    if caller_names.is_some() {
+        execution_context.names = caller_names;
+    }
+    match result? {
+        Flow::Next => Ok(None),
+        Flow::Return(value) => Ok(value),
+    }
+}
+
+impl<'a> Expression<'a, Slot<'a>> {
+    fn evaluate(
+        &self,
+        execution_context: &mut ExecutionContext<'a>,
+    ) -> Result<Value, ExecutionError> {
+        match self {
+            &Expression::Constant(x) => Ok(Value::Integer(x)),
+            &Expression::RealConstant(x) => Ok(Value::Real(x)),
+            &Expression::StringConstant(text) => Ok(Value::String(unescape(text))),
+            &Expression::Variable(var) => Ok(execution_context.variable(var)?.clone()),
+            &Expression::Index(var, ref indices) => {
+                let positions = positions(var.name, indices, execution_context)?;
+                element(var.name, execution_context.variable(var)?, &positions)
+            }
+            Expression::Minus(expr) => negate(expr.evaluate(execution_context)?),
+            Expression::Addition(expr1, expr2) => {
+                let val1 = expr1.evaluate(execution_context)?;
+                let val2 = expr2.evaluate(execution_context)?;
+                arithmetic(ArithmeticOperation::Addition, val1, val2)
+            }
+            Expression::Subtraction(expr1, expr2) => {
+                let val1 = expr1.evaluate(execution_context)?;
+                let val2 = expr2.evaluate(execution_context)?;
+                arithmetic(ArithmeticOperation::Subtraction, val1, val2)
+            }
+            Expression::Multiplication(expr1, expr2) => {
+                let val1 = expr1.evaluate(execution_context)?;
+                let val2 = expr2.evaluate(execution_context)?;
+                arithmetic(ArithmeticOperation::Multiplication, val1, val2)
+            }
+            Expression::Division(expr1, expr2) => {
+                let val1 = expr1.evaluate(execution_context)?;
+                let val2 = expr2.evaluate(execution_context)?;
+                arithmetic(ArithmeticOperation::Division, val1, val2)
+            }
+            Expression::Reminder(expr1, expr2) => {
+                let val1 = expr1.evaluate(execution_context)?;
+                let val2 = expr2.evaluate(execution_context)?;
+                arithmetic(ArithmeticOperation::Reminder, val1, val2)
+            }
+            Expression::Order(order_type, expr1, expr2) => {
+                let val1 = expr1.evaluate(execution_context)?;
+                let val2 = expr2.evaluate(execution_context)?;
+                order(order_type, val1, val2)
+            }
+            Expression::And(expr1, expr2) => {
+                let cond = expr1.evaluate(execution_context)?.is_true()?
+                    && expr2.evaluate(execution_context)?.is_true()?;
+                Ok(Value::from(cond))
+            }
+            Expression::Or(expr1, expr2) => {
+                let cond = expr1.evaluate(execution_context)?.is_true()?
+                    || expr2.evaluate(execution_context)?.is_true()?;
+                Ok(Value::from(cond))
+            }
+            Expression::Not(expr) => {
+                let cond = expr.evaluate(execution_context)?.is_true()?;
+                Ok(Value::from(!cond))
+            }
+            Expression::IntegerPart(expr) => integer_part(expr.evaluate(execution_context)?),
+            Expression::Absolute(expr) => absolute(expr.evaluate(execution_context)?),
+            Expression::Call(name, arguments) => call(name, arguments, execution_context)?
+                .ok_or_else(|| ExecutionErrorKind::NoReturnValueError(name.to_string()).into()),
+        }
+    }
+}
+
+fn negate(value: Value) -> Result<Value, ExecutionError> {
+    match value {
+        Value::Integer(x) => x.checked_neg().map(Value::Integer).ok_or_else(overflow),
+        Value::Real(x) => Ok(Value::Real(-x)),
+        value => Err(value.number_expected().into()),
+    }
+}
+
+fn integer_part(value: Value) -> Result<Value, ExecutionError> {
+    match value {
+        Value::Integer(x) => Ok(Value::Integer(x)),
+        Value::Real(x) if x.floor() >= i32::MIN as f64 && x.floor() <= i32::MAX as f64 => {
+            Ok(Value::Integer(x.floor() as i32))
+        }
+        Value::Real(_) => Err(overflow()),
+        value => Err(value.number_expected().into()),
+    }
+}
+
+fn absolute(value: Value) -> Result<Value, ExecutionError> {
+    match value {
+        Value::Integer(x) => x.checked_abs().map(Value::Integer).ok_or_else(overflow),
+        Value::Real(x) => Ok(Value::Real(x.abs())),
+        value => Err(value.number_expected().into()),
+    }
+}
+
+/// Integers and strings are compared among themselves, any other numbers as
+/// reals.
+fn order(order_type: &OrderType, val1: Value, val2: Value) -> Result<Value, ExecutionError> {
+    let cond = match (val1, val2) {
+        (Value::Integer(x), Value::Integer(y)) => compare(order_type, x, y),
+        (Value::String(x), Value::String(y)) => compare(order_type, x, y),
+        (x, y) => compare(order_type, x.as_real()?, y.as_real()?),
+    };
+    Ok(Value::from(cond))
+}
+
+fn call_built_in(name: &str, arguments: &[Value]) -> Result<Value, ExecutionError> {
+    let expect_arguments = |count: usize| {
+        if arguments.len() == count {
+            Ok(())
+        } else {
+            Err(ExecutionErrorKind::ArgumentCountError(
+                name.to_string(),
+                count,
+                arguments.len(),
+            ))
+        }
+    };
+    match name {
+        "sqrt" | "radical" => {
+            expect_arguments(1)?;
+            let x = arguments[0].as_real()?;
+            if x < 0.0 {
+                Err(ExecutionErrorKind::NegativeSquareRootError.into())
+            } else {
+                Ok(Value::Real(x.sqrt()))
+            }
+        }
+        "abs" | "modul" => {
+            expect_arguments(1)?;
+            absolute(arguments[0].clone())
+        }
+        "lungime" => {
+            expect_arguments(1)?;
+            match &arguments[0] {
+                Value::String(text) => Ok(Value::Integer(text.chars().count() as i32)),
+                value => Err(ExecutionErrorKind::TypeError("un text", value.type_name()).into()),
+            }
+        }
+        "max" | "min" => {
+            if arguments.is_empty() {
+                return Err(ExecutionErrorKind::ArgumentCountError(name.to_string(), 1, 0).into());
+            }
+            let order_type = if name == "max" {
+                OrderType::Greater
+            } else {
+                OrderType::Less
+            };
+            let is_real = arguments.iter().any(|x| matches!(x, Value::Real(_)));
+            let mut result = &arguments[0];
+            result.as_real()?;
+            for argument in &arguments[1..] {
+                if compare(&order_type, argument.as_real()?, result.as_real()?) {
+                    result = argument;
+                }
+            }
+            if is_real {
+                Ok(Value::Real(result.as_real()?))
+            } else {
+                Ok(result.clone())
+            }
+        }
+        _ => Err(ExecutionErrorKind::UnknownFunctionError(name.to_string()).into()),
+    }
+}
+
+/// Replaces the escape sequences of a string literal with the characters they
+/// stand for. Unknown sequences are left as they are.
+pub fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(c @ ('\\' | '\'' | '"')) => result.push(c),
+            Some(c) => {
+                result.push('\\');
+                result.push(c);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Stores `value` into a variable or into an array element. Assigning to an
+/// element of a new variable creates the array, and assigning past its end
+/// grows it, leaving the elements in between uninitialized. A matrix is an
+/// array whose elements are the arrays of its lines. The characters of a
+/// string can be replaced, but a string does not grow.
+fn store<'a>(
+    target: &'a LValue<'a, Slot<'a>>,
+    value: Value,
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<(), ExecutionError> {
+    match target {
+        LValue::Variable(var) => {
+            let index = execution_context.index(*var);
+            execution_context.variables[index] = Some(value);
+        }
+        LValue::Index(var, indices) => {
+            let positions = positions(var.name, indices, execution_context)?;
+            let index = execution_context.index(*var);
+            let root =
+                execution_context.variables[index].get_or_insert_with(|| Value::Array(Vec::new()));
+            store_element(var.name, root, &positions, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the element of `root` at `positions`. The characters of a string are
+/// strings of length one.
+fn element(var: &str, root: &Value, positions: &[usize]) -> Result<Value, ExecutionError> {
+    let mut value = root;
+    let mut character;
+    for (depth, &position) in positions.iter().enumerate() {
+        value = match value {
+            Value::Array(elements) => match elements.get(position) {
+                Some(Some(element)) => element,
+                _ => {
+                    let name = element_name(var, &positions[..=depth]);
+                    return Err(ExecutionErrorKind::UninitializedElementError(name).into());
+                }
+            },
+            Value::String(text) => {
+                let c = text.chars().nth(position).ok_or_else(|| {
+                    ExecutionErrorKind::IndexOutOfBoundsError(var.to_string(), position as i32)
+                })?;
+                character = Value::String(c.to_string());
+                &character
+            }
+            _ => {
+                let name = element_name(var, &positions[..depth]);
+                return Err(ExecutionErrorKind::NotAnArrayError(name).into());
+            }
+        };
+    }
+    Ok(value.clone())
+}
+
+/// Stores `value` into the element of `root` at `positions`, growing the
+/// arrays on the way as needed.
+fn store_element(
+    var: &str,
+    root: &mut Value,
+    positions: &[usize],
+    value: Value,
+) -> Result<(), ExecutionError> {
+    let mut slot = root;
+    for (depth, &position) in positions.iter().enumerate() {
+        slot = match slot {
+            Value::Array(elements) => {
+                if position >= elements.len() {
+                    elements.resize(position + 1, None);
+                }
+                elements[position].get_or_insert_with(|| Value::Array(Vec::new()))
+            }
+            Value::String(text) if depth + 1 == positions.len() => {
+                return replace_character(var, text, position, value);
+            }
+            _ => {
+                let name = element_name(var, &positions[..depth]);
+                return Err(ExecutionErrorKind::NotAnArrayError(name).into());
+            }
+        };
+    }
+    *slot = value;
+    Ok(())
+}
+
+fn replace_character(
+    var: &str,
+    text: &mut String,
+    position: usize,
+    value: Value,
+) -> Result<(), ExecutionError> {
+    let mut characters: Vec<char> = text.chars().collect();
+    if position >= characters.len() {
+        return Err(
+            ExecutionErrorKind::IndexOutOfBoundsError(var.to_string(), position as i32).into(),
+        );
+    }
+    match value {
+        Value::String(ref character) if character.chars().count() == 1 => {
+            characters[position] = character.chars().next().unwrap();
+        }
+        value => {
+            return Err(ExecutionErrorKind::TypeError("un caracter", value.type_name()).into())
+        }
+    }
+    *text = characters.into_iter().collect();
+    Ok(())
+}
+
+fn positions<'a>(
+    var: &str,
+    indices: &[Expression<'a, Slot<'a>>],
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<Vec<usize>, ExecutionError> {
+    let mut positions = Vec::with_capacity(indices.len());
+    for index in indices {
+        positions.push(position(var, index.evaluate(execution_context)?)?);
+    }
+    Ok(positions)
+}
+
+/// How an element is written in the source, such as `a[1][2]`.
+fn element_name(var: &str, positions: &[usize]) -> String {
+    let mut name = var.to_string();
+    for position in positions {
+        name += &format!("[{}]", position);
+    }
+    name
+}
+
+/// Checks that `index` can be used to access an element of the array `var`.
+fn position(var: &str, index: Value) -> Result<usize, ExecutionErrorKind> {
+    match index {
+        Value::Integer(x) if x >= 0 && (x as usize) < MAX_ARRAY_LENGTH => Ok(x as usize),
+        Value::Integer(x) => Err(ExecutionErrorKind::IndexOutOfBoundsError(
+            var.to_string(),
+            x,
+        )),
+        Value::Real(_) => Err(ExecutionErrorKind::IndexNotIntegerError(var.to_string())),
+        value => Err(value.number_expected()),
+    }
+}
+
+fn compare<T: PartialOrd>(order_type: &OrderType, x: T, y: T) -> bool {
+    match order_type {
+        OrderType::Less => x < y,
+        OrderType::LessOrEqual => x <= y,
+        OrderType::Equal => x == y,
+        OrderType::NotEqual => x != y,
+        OrderType::GreaterOrEqual => x >= y,
+        OrderType::Greater => x > y,
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ArithmeticOperation {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division,
+    Reminder,
+}
+
+/// Integers stay integers, while any real operand turns the result into a real.
+/// This makes `7 / 2` an integer division and `7 / 2.0` a real one. Strings
+/// can only be added together, which concatenates them.
+fn arithmetic(
+    operation: ArithmeticOperation,
+    val1: Value,
+    val2: Value,
+) -> Result<Value, ExecutionError> {
+    match (val1, val2) {
+        (Value::Integer(x), Value::Integer(y)) => match operation {
+            ArithmeticOperation::Addition => {
+                x.checked_add(y).map(Value::Integer).ok_or_else(overflow)
+            }
+            ArithmeticOperation::Subtraction => {
+                x.checked_sub(y).map(Value::Integer).ok_or_else(overflow)
+            }
+            ArithmeticOperation::Multiplication => {
+                x.checked_mul(y).map(Value::Integer).ok_or_else(overflow)
+            }
+            ArithmeticOperation::Division if y == 0 => {
+                Err(ExecutionErrorKind::ZeroDivisionError.into())
+            }
+            ArithmeticOperation::Division => {
+                x.checked_div(y).map(Value::Integer).ok_or_else(overflow)
+            }
+            ArithmeticOperation::Reminder if y == 0 => {
+                Err(ExecutionErrorKind::ZeroDivisionError.into())
+            }
+            // Only the smallest integer divided by -1 wraps, and its remainder is 0.
+            ArithmeticOperation::Reminder => Ok(Value::Integer(x.wrapping_rem(y))),
+        },
+        (Value::String(x), val2) => match (operation, val2) {
+            (ArithmeticOperation::Addition, Value::String(y)) => Ok(Value::String(x + &y)),
+            (ArithmeticOperation::Addition, val2) => {
+                Err(ExecutionErrorKind::TypeError("un text", val2.type_name()).into())
+            }
+            _ => Err(ExecutionErrorKind::TypeError("un numar", "un text").into()),
+        },
+        (val1, val2) => {
+            let (x, y) = (val1.as_real()?, val2.as_real()?);
+            match operation {
+                ArithmeticOperation::Addition => Ok(Value::Real(x + y)),
+                ArithmeticOperation::Subtraction => Ok(Value::Real(x - y)),
+                ArithmeticOperation::Multiplication => Ok(Value::Real(x * y)),
+                ArithmeticOperation::Division if y == 0.0 => {
+                    Err(ExecutionErrorKind::ZeroDivisionError.into())
+                }
+                ArithmeticOperation::Division => Ok(Value::Real(x / y)),
+                ArithmeticOperation::Reminder => Err(ExecutionErrorKind::RealReminderError.into()),
+            }
+        }
+    }
+}
+
+fn overflow() -> ExecutionError {
+    ExecutionErrorKind::OverflowError.into()
+}
+
+#[derive(Debug)]
+pub struct ExecutionError {
+    pub kind: ExecutionErrorKind,
+    pub location: Option<Location>,
+}
+
+/// The instruction an execution error was raised by.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Location {
+    pub line: usize,
+    pub source: String,
+}
+
+impl ExecutionError {
+    /// Attaches the location of `span` unless the error already has a more
+    /// precise one, coming from an instruction nested inside it.
+    fn located(mut self, span: Span, program: &str) -> Self {
+        if self.location.is_none() {
+            self.location = span.line(program).map(|line| Location {
+                line,
+                source: span.0.lines().next().unwrap_or_default().to_string(),
+            });
+        }
+        self
+    }
+}
+
+impl From<ExecutionErrorKind> for ExecutionError {
+    fn from(kind: ExecutionErrorKind) -> Self {
+        ExecutionError {
+            kind,
+            location: None,
+        }
+    }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(
+                f,
+                "Linia {}: {}\n  {}",
+                location.line, self.kind, location.source
+            ),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum ExecutionErrorKind {
+    ZeroDivisionError,
+    VariableNotDefinedError(String),
+    ReadingError,
+    NumberParsingError(String),
+    EndOfInputError,
+    RealReminderError,
+    NegativeSquareRootError,
+    UnknownFunctionError(String),
+    ArgumentCountError(String, usize, usize),
+    VariableStepInLoop,
+    TypeError(&'static str, &'static str),
+    NotAnArrayError(String),
+    IndexNotIntegerError(String),
+    IndexOutOfBoundsError(String, i32),
+    UninitializedElementError(String),
+    NoReturnValueError(String),
+    CallDepthError(usize),
+    /// The program was stopped after the given number of steps.
+    StepLimitExceeded(u64),
+    /// The program ran out of time, after the given number of steps.
+    TimeLimitExceeded(u64),
+    Cancelled,
+    /// An integer result does not fit in an integer value.
+    OverflowError,
+}
+
+impl fmt::Display for ExecutionErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionErrorKind::ZeroDivisionError => write!(f, "impartire la zero"),
+            ExecutionErrorKind::VariableNotDefinedError(var) => {
+                write!(f, "variabila '{}' nu a fost initializata", var)
+            }
+            ExecutionErrorKind::ReadingError => write!(f, "nu s-a putut citi datele de intrare"),
+            ExecutionErrorKind::NumberParsingError(token) => {
+                write!(f, "valoarea citita '{}' nu este un numar", token)
+            }
+            ExecutionErrorKind::EndOfInputError => {
+                write!(f, "nu mai sunt date de intrare de citit")
+            }
+            ExecutionErrorKind::RealReminderError => {
+                write!(
+                    f,
+                    "restul impartirii se poate calcula doar pentru numere intregi"
+                )
+            }
+            ExecutionErrorKind::NegativeSquareRootError => {
+                write!(f, "radical dintr-un numar negativ")
+            }
+            ExecutionErrorKind::UnknownFunctionError(name) => {
+                write!(f, "functia '{}' nu exista", name)
+            }
+            ExecutionErrorKind::ArgumentCountError(name, expected, found) => write!(
+                f,
+                "functia '{}' primeste {} argumente, dar a fost apelata cu {}",
+                name, expected, found
+            ),
+            ExecutionErrorKind::VariableStepInLoop => {
+                write!(
+                    f,
+                    "pasul structurii pentru s-a modificat in timpul executiei"
+                )
+            }
+            ExecutionErrorKind::TypeError(expected, found) => {
+                write!(f, "se astepta {}, dar valoarea este {}", expected, found)
+            }
+            ExecutionErrorKind::NotAnArrayError(name) => {
+                write!(f, "'{}' nu este un tablou", name)
+            }
+            ExecutionErrorKind::IndexNotIntegerError(var) => {
+                write!(
+                    f,
+                    "indicele tabloului '{}' trebuie sa fie un numar intreg",
+                    var
+                )
+            }
+            ExecutionErrorKind::IndexOutOfBoundsError(var, index) => {
+                write!(f, "indicele {} este in afara tabloului '{}'", index, var)
+            }
+            ExecutionErrorKind::NoReturnValueError(name) => {
+                write!(f, "subprogramul '{}' nu a returnat nicio valoare", name)
+            }
+            ExecutionErrorKind::CallDepthError(depth) => {
+                write!(f, "s-au depasit {} apeluri de subprograme imbricate", depth)
+            }
+            ExecutionErrorKind::UninitializedElementError(element) => {
+                write!(f, "elementul {} nu a fost initializat", element)
+            }
+            ExecutionErrorKind::StepLimitExceeded(steps) => {
+                write!(f, "programul a fost oprit dupa {} pasi", steps)
+            }
+            ExecutionErrorKind::TimeLimitExceeded(steps) => write!(
+                f,
+                "programul a depasit timpul de executie, dupa {} pasi",
+                steps
+            ),
+            ExecutionErrorKind::Cancelled => write!(f, "programul a fost oprit"),
+            ExecutionErrorKind::OverflowError => {
+                write!(f, "rezultatul este prea mare pentru un numar intreg")
+            }
+        }
+    }
+}
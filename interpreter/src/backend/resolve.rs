@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crate::frontend::expression::{Expression, LValue};
+use crate::frontend::instruction::{Block, Instruction, Statement, Subprogram, Writable};
+
+/// A variable, numbered among the variables of the main program or of the
+/// subprogram it belongs to. The name is kept for error messages.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Slot<'a> {
+    pub name: &'a str,
+    pub index: usize,
+}
+
+pub struct Resolved<'a> {
+    pub program: Block<'a, Slot<'a>>,
+    /// The variable names of the main program, by slot.
+    pub variables: Vec<&'a str>,
+    /// The variable names of each subprogram, by slot, starting with its
+    /// parameters. A subprogram defined twice keeps those of its last
+    /// definition, which is the one that gets called.
+    pub subprograms: HashMap<&'a str, Vec<&'a str>>,
+}
+
+/// Numbers the variables of the program, so that they are looked up by slot
+/// instead of by name while the program runs. Each subprogram has its own
+/// variables, separate from those of the main program.
+pub fn resolve(program: Block) -> Resolved {
+    let mut resolver = Resolver {
+        variables: Vec::new(),
+        subprograms: HashMap::new(),
+    };
+    let program = resolver.block(program);
+    Resolved {
+        program,
+        variables: resolver.variables,
+        subprograms: resolver.subprograms,
+    }
+}
+
+struct Resolver<'a> {
+    /// The variables of the main program or of the subprogram being resolved.
+    variables: Vec<&'a str>,
+    subprograms: HashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> Resolver<'a> {
+    fn slot(&mut self, name: &'a str) -> Slot<'a> {
+        let index = match self.variables.iter().position(|&variable| variable == name) {
+            Some(index) => index,
+            None => {
+                self.variables.push(name);
+                self.variables.len() - 1
+            }
+        };
+        Slot { name, index }
+    }
+
+    fn block(&mut self, block: Block<'a>) -> Block<'a, Slot<'a>> {
+        block
+            .into_iter()
+            .map(|statement| Statement {
+                instruction: self.instruction(statement.instruction),
+                span: statement.span,
+            })
+            .collect()
+    }
+
+    fn instruction(&mut self, instruction: Instruction<'a>) -> Instruction<'a, Slot<'a>> {
+        match instruction {
+            Instruction::Read(targets) => {
+                Instruction::Read(targets.into_iter().map(|t| self.lvalue(t)).collect())
+            }
+            Instruction::Write(writables, newline) => {
+                let writables = writables
+                    .into_iter()
+                    .map(|writable| match writable {
+                        Writable::Expression(expr) => Writable::Expression(self.expression(expr)),
+                        Writable::String(text) => Writable::String(text),
+                    })
+                    .collect();
+                Instruction::Write(writables, newline)
+            }
+            Instruction::Assignment(target, expr) => {
+                let target = self.lvalue(target);
+                Instruction::Assignment(target, self.expression(expr))
+            }
+            Instruction::If(cond, if_block, else_block) => Instruction::If(
+                self.expression(cond),
+                self.block(if_block),
+                else_block.map(|block| self.block(block)),
+            ),
+            Instruction::While(while_type, cond, block) => {
+                let cond = self.expression(cond);
+                Instruction::While(while_type, cond, self.block(block))
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => Instruction::For {
+                variable: self.slot(variable),
+                start_expr: self.expression(start_expr),
+                end_expr: self.expression(end_expr),
+                step: self.expression(step),
+                block: self.block(block),
+            },
+            Instruction::Call(name, arguments) => Instruction::Call(name, self.list(arguments)),
+            Instruction::Return(expr) => Instruction::Return(expr.map(|e| self.expression(e))),
+            Instruction::Subprogram(subprogram) => {
+                let main = std::mem::take(&mut self.variables);
+                let parameters = subprogram
+                    .parameters
+                    .into_iter()
+                    .map(|parameter| self.slot(parameter))
+                    .collect();
+                let block = self.block(subprogram.block);
+                let variables = std::mem::replace(&mut self.variables, main);
+                self.subprograms.insert(subprogram.name, variables);
+                Instruction::Subprogram(Subprogram {
+                    name: subprogram.name,
+                    parameters,
+                    block,
+                })
+            }
+        }
+    }
+
+    fn lvalue(&mut self, target: LValue<'a>) -> LValue<'a, Slot<'a>> {
+        match target {
+            LValue::Variable(var) => LValue::Variable(self.slot(var)),
+            LValue::Index(var, indices) => LValue::Index(self.slot(var), self.list(indices)),
+        }
+    }
+
+    fn list(&mut self, exprs: Vec<Expression<'a>>) -> Vec<Expression<'a, Slot<'a>>> {
+        exprs.into_iter().map(|e| self.expression(e)).collect()
+    }
+
+    fn boxed(&mut self, expr: Expression<'a>) -> Box<Expression<'a, Slot<'a>>> {
+        Box::new(self.expression(expr))
+    }
+
+    fn expression(&mut self, expr: Expression<'a>) -> Expression<'a, Slot<'a>> {
+        match expr {
+            Expression::Constant(x) => Expression::Constant(x),
+            Expression::RealConstant(x) => Expression::RealConstant(x),
+            Expression::StringConstant(text) => Expression::StringConstant(text),
+            Expression::Variable(var) => Expression::Variable(self.slot(var)),
+            Expression::Index(var, indices) => {
+                Expression::Index(self.slot(var), self.list(indices))
+            }
+            Expression::Multiplication(x, y) => {
+                Expression::Multiplication(self.boxed(*x), self.boxed(*y))
+            }
+            Expression::Division(x, y) => Expression::Division(self.boxed(*x), self.boxed(*y)),
+            Expression::Addition(x, y) => Expression::Addition(self.boxed(*x), self.boxed(*y)),
+            Expression::Subtraction(x, y) => {
+                Expression::Subtraction(self.boxed(*x), self.boxed(*y))
+            }
+            Expression::Reminder(x, y) => Expression::Reminder(self.boxed(*x), self.boxed(*y)),
+            Expression::Minus(x) => Expression::Minus(self.boxed(*x)),
+            Expression::Order(order_type, x, y) => {
+                Expression::Order(order_type, self.boxed(*x), self.boxed(*y))
+            }
+            Expression::And(x, y) => Expression::And(self.boxed(*x), self.boxed(*y)),
+            Expression::Or(x, y) => Expression::Or(self.boxed(*x), self.boxed(*y)),
+            Expression::Not(x) => Expression::Not(self.boxed(*x)),
+            Expression::IntegerPart(x) => Expression::IntegerPart(self.boxed(*x)),
+            Expression::Absolute(x) => Expression::Absolute(self.boxed(*x)),
+            Expression::Call(name, arguments) => Expression::Call(name, self.list(arguments)),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use super::bytecode::{Op, Program};
+use super::{
+    absolute, arithmetic, call_built_in, element, integer_part, negate, order, position,
+    store_element, ArithmeticOperation, ExecutionContext, ExecutionError, ExecutionErrorKind,
+    Value, MAX_CALL_DEPTH,
+};
+
+struct Frame {
+    chunk: usize,
+    /// The next operation to execute.
+    pc: usize,
+    slots: Vec<Option<Value>>,
+    /// Whether the caller uses the returned value.
+    result: bool,
+}
+
+struct Machine<'p, 'a> {
+    program: &'p Program<'a>,
+    stack: Vec<Value>,
+    /// Array positions computed for the next element access.
+    positions: Vec<usize>,
+    frames: Vec<Frame>,
+}
+
+/// Runs a compiled program. Errors are located at the instruction the failing
+/// operation was compiled from, so they match those of `execute_program`.
+pub fn run<'a>(
+    program: &Program<'a>,
+    execution_context: &mut ExecutionContext<'a>,
+) -> Result<(), ExecutionError> {
+    let mut machine = Machine {
+        program,
+        stack: Vec::new(),
+        positions: Vec::new(),
+        frames: Vec::new(),
+    };
+    machine.enter(0, Vec::new(), false);
+    loop {
+        match machine.step(execution_context) {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(e) => {
+                let frame = machine.frames.last().unwrap();
+                let span = program.chunks[frame.chunk].spans[frame.pc - 1];
+                return Err(e.located(span, execution_context.program));
+            }
+        }
+    }
+}
+
+impl<'p, 'a> Machine<'p, 'a> {
+    fn enter(&mut self, chunk: usize, arguments: Vec<Value>, result: bool) {
+        let mut slots = vec![None; self.program.chunks[chunk].slots.len()];
+        for (slot, argument) in slots.iter_mut().zip(arguments) {
+            *slot = Some(argument);
+        }
+        self.frames.push(Frame {
+            chunk,
+            pc: 0,
+            slots,
+            result,
+        });
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("the stack is empty")
+    }
+
+    fn pop_positions(&mut self, count: usize) -> Vec<usize> {
+        self.positions.split_off(self.positions.len() - count)
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn name(&self, slot: usize) -> &'a str {
+        self.program.chunks[self.frames.last().unwrap().chunk].slots[slot]
+    }
+
+    fn load(&mut self, slot: usize) -> Result<&mut Value, ExecutionErrorKind> {
+        let name = self.name(slot);
+        self.frame().slots[slot]
+            .as_mut()
+            .ok_or_else(|| ExecutionErrorKind::VariableNotDefinedError(name.to_string()))
+    }
+
+    /// Executes one operation, returning whether the program has ended.
+    fn step(
+        &mut self,
+        execution_context: &mut ExecutionContext<'a>,
+    ) -> Result<bool, ExecutionError> {
+        let program = self.program;
+        let frame = self.frame();
+        let op = &program.chunks[frame.chunk].code[frame.pc];
+        frame.pc += 1;
+        match op {
+            &Op::Push(constant) => self.stack.push(program.constants[constant].clone()),
+            &Op::Load(slot) => {
+                let value = self.load(slot)?.clone();
+                self.stack.push(value);
+            }
+            &Op::Store(slot) => {
+                let value = self.pop();
+                self.frame().slots[slot] = Some(value);
+            }
+            Op::Pop => {
+                self.pop();
+            }
+            &Op::Position(slot) => {
+                let index = self.pop();
+                let position = position(self.name(slot), index)?;
+                self.positions.push(position);
+            }
+            &Op::LoadElement(slot, count) => {
+                let positions = self.pop_positions(count);
+                let name = self.name(slot);
+                let value = element(name, self.load(slot)?, &positions)?;
+                self.stack.push(value);
+            }
+            &Op::StoreElement(slot, count) => {
+                let positions = self.pop_positions(count);
+                let value = self.pop();
+                let name = self.name(slot);
+                let root = self.frame().slots[slot].get_or_insert_with(|| Value::Array(Vec::new()));
+                store_element(name, root, &positions, value)?;
+            }
+            Op::Read => {
+                let token = execution_context.read_token()?;
+                self.stack.push(Value::parse(&token)?);
+            }
+            Op::Write => {
+                let value = self.pop();
+                if let Value::Array(_) = value {
+                    return Err(value.number_expected().into());
+                }
+                execution_context
+                    .output
+                    .write_all(value.to_string().as_bytes())
+                    .unwrap();
+            }
+            Op::Newline => execution_context.output.write_all(b"\n").unwrap(),
+            Op::Negate => {
+                let value = negate(self.pop())?;
+                self.stack.push(value);
+            }
+            Op::IntegerPart => {
+                let value = integer_part(self.pop())?;
+                self.stack.push(value);
+            }
+            Op::Absolute => {
+                let value = absolute(self.pop())?;
+                self.stack.push(value);
+            }
+            Op::Not => {
+                let cond = self.pop().is_true()?;
+                self.stack.push(Value::from(!cond));
+            }
+            Op::Truth => {
+                let cond = self.pop().is_true()?;
+                self.stack.push(Value::from(cond));
+            }
+            &Op::Arithmetic(operation) => {
+                let val2 = self.pop();
+                let val1 = self.pop();
+                self.stack.push(arithmetic(operation, val1, val2)?);
+            }
+            Op::Order(order_type) => {
+                let val2 = self.pop();
+                let val1 = self.pop();
+                self.stack.push(order(order_type, val1, val2)?);
+            }
+            &Op::Jump(target) => self.frame().pc = target,
+            &Op::JumpIfFalse(target) => {
+                if !self.pop().is_true()? {
+                    self.frame().pc = target;
+                }
+            }
+            &Op::JumpIfTrue(target) => {
+                if self.pop().is_true()? {
+                    self.frame().pc = target;
+                }
+            }
+            &Op::CallBuiltIn(name, count) => {
+                let arguments = self.stack.split_off(self.stack.len() - count);
+                self.stack.push(call_built_in(name, &arguments)?);
+            }
+            &Op::Call {
+                chunk,
+                arguments: count,
+                result,
+            } => {
+                let callee = &program.chunks[chunk];
+                if count != callee.parameters {
+                    return Err(ExecutionErrorKind::ArgumentCountError(
+                        callee.name.to_string(),
+                        callee.parameters,
+                        count,
+                    )
+                    .into());
+                }
+                if self.frames.len() > MAX_CALL_DEPTH {
+                    return Err(ExecutionErrorKind::CallDepthError(MAX_CALL_DEPTH).into());
+                }
+                let arguments = self.stack.split_off(self.stack.len() - count);
+                self.enter(chunk, arguments, result);
+            }
+            &Op::Return(has_value) => {
+                let value = if has_value { Some(self.pop()) } else { None };
+                let frame = self.frames.pop().unwrap();
+                if self.frames.is_empty() {
+                    return Ok(true);
+                }
+                if frame.result {
+                    let name = program.chunks[frame.chunk].name;
+                    let value = value
+                        .ok_or_else(|| ExecutionErrorKind::NoReturnValueError(name.to_string()))?;
+                    self.stack.push(value);
+                }
+            }
+            &Op::ForDirection { step, direction } => {
+                let ascending = self.load(step)?.as_real()? >= 0.0;
+                self.frame().slots[direction] = Some(Value::from(ascending));
+            }
+            &Op::ForCurrent(variable) => {
+                let current = self.load(variable)?.as_real()?;
+                self.stack.push(Value::Real(current));
+            }
+            &Op::ForTest { direction, exit } => {
+                let end = self.pop().as_real()?;
+                let current = self.pop().as_real()?;
+                let in_range = if self.load(direction)?.is_true()? {
+                    current <= end
+                } else {
+                    current >= end
+                };
+                if !in_range {
+                    self.frame().pc = exit;
+                }
+            }
+            &Op::ForStep { variable, step } => {
+                let step = self.load(step)?.clone();
+                let current = self.load(variable)?.clone();
+                let next = arithmetic(ArithmeticOperation::Addition, current, step)?;
+                self.frame().slots[variable] = Some(next);
+            }
+            Op::Tick => execution_context.tick()?,
+            &Op::CheckStep(step) => {
+                let value = self.pop();
+                if *self.load(step)? != value {
+                    return Err(ExecutionErrorKind::VariableStepInLoop.into());
+                }
+            }
+        }
+        Ok(false)
+    }
+}
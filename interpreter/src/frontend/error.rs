@@ -0,0 +1,189 @@
+use nom::error::{ContextError, ErrorKind, ParseError};
+use std::fmt;
+
+/// Reported instead of what was expected when a line does not line up with
+/// any of the blocks it could belong to.
+pub const INCONSISTENT_INDENTATION: &str = "indentare inconsistenta";
+
+/// Reported for an integer literal that does not fit in an integer value.
+pub const NUMBER_TOO_LARGE: &str = "numarul este prea mare";
+
+/// Parser error that remembers the furthest position reached in the input and
+/// everything that was expected there, so that the message points at the real
+/// problem instead of at the last alternative that was tried.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SyntaxError<'a> {
+    pub input: &'a str,
+    pub expected: Vec<String>,
+    /// What is wrong, when it is not about a missing token.
+    pub problem: Option<&'static str>,
+}
+
+impl<'a> SyntaxError<'a> {
+    pub fn into_diagnostic(self, source: &str) -> Diagnostic {
+        let offset = source.len() - self.input.len();
+        let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |index| offset + index);
+        Diagnostic {
+            line: source[..offset].matches('\n').count() + 1,
+            column: source[line_start..offset].chars().count() + 1,
+            expected: self.expected,
+            problem: self.problem.map(str::to_string),
+            snippet: source[line_start..line_end].trim_end().to_string(),
+        }
+    }
+}
+
+impl<'a> ParseError<&'a str> for SyntaxError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        SyntaxError {
+            input,
+            expected: Vec::new(),
+            problem: None,
+        }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(input: &'a str, c: char) -> Self {
+        SyntaxError {
+            input,
+            expected: vec![format!("'{}'", c)],
+            problem: None,
+        }
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        match self.input.len().cmp(&other.input.len()) {
+            std::cmp::Ordering::Less => self,
+            std::cmp::Ordering::Greater => other,
+            std::cmp::Ordering::Equal => {
+                for expected in other.expected {
+                    if !self.expected.contains(&expected) {
+                        self.expected.push(expected);
+                    }
+                }
+                self
+            }
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for SyntaxError<'a> {
+    /// A context describes what was expected only when its parser failed
+    /// right away; errors found deeper inside it are more precise and are kept,
+    /// and so are problems. Single words are tokens and get quoted, longer
+    /// labels are descriptions.
+    fn add_context(input: &'a str, ctx: &'static str, other: Self) -> Self {
+        if input.len() != other.input.len() || other.problem.is_some() {
+            return other;
+        }
+        if ctx == INCONSISTENT_INDENTATION || ctx == NUMBER_TOO_LARGE {
+            return SyntaxError {
+                input,
+                expected: Vec::new(),
+                problem: Some(ctx),
+            };
+        }
+        let label = if ctx.contains(' ') {
+            ctx.to_string()
+        } else {
+            format!("'{}'", ctx)
+        };
+        SyntaxError {
+            input,
+            expected: vec![label],
+            problem: None,
+        }
+    }
+}
+
+/// A syntax error located in the source, ready to be shown to the user.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub expected: Vec<String>,
+    pub problem: Option<String>,
+    pub snippet: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Linia {}, coloana {}: ", self.line, self.column)?;
+        if let Some(problem) = &self.problem {
+            write!(f, "{}", problem)?;
+        } else {
+            match self.expected.split_last() {
+                None => write!(f, "eroare de sintaxa")?,
+                Some((last, [])) => write!(f, "se astepta {}", last)?,
+                Some((last, rest)) => write!(f, "se astepta {} sau {}", rest.join(", "), last)?,
+            }
+        }
+        write!(
+            f,
+            "\n  {}\n  {}^",
+            self.snippet,
+            " ".repeat(self.column - 1)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frontend::instruction::program;
+
+    fn diagnostic(source: &str) -> Diagnostic {
+        program::<SyntaxError>(source)
+            .unwrap_err()
+            .into_diagnostic(source)
+    }
+
+    #[test]
+    fn diagnostic_test() {
+        assert_eq!(
+            diagnostic("x <- 1\ndaca x > 0\n  scrie x"),
+            Diagnostic {
+                line: 2,
+                column: 11,
+                expected: vec!["'atunci'".to_string()],
+                problem: None,
+                snippet: "daca x > 0".to_string(),
+            }
+        );
+        assert_eq!(
+            diagnostic("scrie 1\ncat timp x executa\n  x <- x *\n").to_string(),
+            "Linia 3, coloana 11: se astepta o expresie\n    x <- x *\n            ^"
+        );
+        assert_eq!(
+            diagnostic("citeste a,\n").to_string(),
+            "Linia 1, coloana 11: se astepta un identificator\n  citeste a,\n            ^"
+        );
+        assert_eq!(
+            diagnostic("scrie max(1, 2").to_string(),
+            "Linia 1, coloana 15: se astepta ')'\n  scrie max(1, 2\n                ^"
+        );
+        assert_eq!(
+            diagnostic("x <- 1\n  y <- 2").to_string(),
+            "Linia 2, coloana 3: indentare inconsistenta\n    y <- 2\n    ^"
+        );
+        assert_eq!(
+            diagnostic("daca x atunci\n    daca y atunci\n        x <- 1\n      y <- 2")
+                .to_string(),
+            "Linia 4, coloana 7: indentare inconsistenta\n        y <- 2\n        ^"
+        );
+        assert_eq!(
+            diagnostic("x <- 2147483648 - 1").to_string(),
+            "Linia 1, coloana 6: numarul este prea mare\n  x <- 2147483648 - 1\n       ^"
+        );
+        assert_eq!(
+            diagnostic("pentru i <- 1 executa\n  scrie i").expected,
+            vec!["','"]
+        );
+    }
+}
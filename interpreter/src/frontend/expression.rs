@@ -1,28 +1,46 @@
-use nom::error::ParseError;
+use super::error::NUMBER_TOO_LARGE;
+use super::keyword::{is_identifier_char, keyword};
+use super::whitespace::space0;
+use nom::error::{context, ContextError, ParseError};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while},
-    character::{
-        complete::{char, digit1, space0},
-        is_alphabetic, is_alphanumeric,
-    },
-    combinator::{fail, map},
-    multi::fold_many0,
-    sequence::{delimited, pair},
+    bytes::complete::{escaped, is_not, tag, take_while},
+    character::complete::{anychar, char, digit1},
+    combinator::{cut, fail, map, opt, peek, recognize, verify},
+    multi::{fold_many0, fold_many1, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
     IResult,
 };
 
+/// An expression, whose variables are referred to by name once parsed and by
+/// their slots once resolved.
 #[derive(PartialEq, Clone, Debug)]
-pub enum Expression<'a> {
-    Constant(u32),
-    Variable(&'a str),
-    Multiplication(Box<Expression<'a>>, Box<Expression<'a>>),
-    Division(Box<Expression<'a>>, Box<Expression<'a>>),
-    Addition(Box<Expression<'a>>, Box<Expression<'a>>),
-    Subtraction(Box<Expression<'a>>, Box<Expression<'a>>),
-    Reminder(Box<Expression<'a>>, Box<Expression<'a>>),
-    Minus(Box<Expression<'a>>),
-    Order(OrderType, Box<Expression<'a>>, Box<Expression<'a>>),
+pub enum Expression<'a, V = &'a str> {
+    Constant(i32),
+    RealConstant(f64),
+    StringConstant(&'a str),
+    Variable(V),
+    Multiplication(Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    Division(Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    Addition(Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    Subtraction(Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    Reminder(Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    Minus(Box<Expression<'a, V>>),
+    Order(OrderType, Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    And(Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    Or(Box<Expression<'a, V>>, Box<Expression<'a, V>>),
+    Not(Box<Expression<'a, V>>),
+    IntegerPart(Box<Expression<'a, V>>),
+    Absolute(Box<Expression<'a, V>>),
+    Call(&'a str, Vec<Expression<'a, V>>),
+    Index(V, Vec<Expression<'a, V>>),
+}
+
+/// Something a value can be stored into, by `citeste` or by an assignment.
+#[derive(PartialEq, Clone, Debug)]
+pub enum LValue<'a, V = &'a str> {
+    Variable(V),
+    Index(V, Vec<Expression<'a, V>>),
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -30,6 +48,7 @@ pub enum OrderType {
     Less,
     LessOrEqual,
     Equal,
+    NotEqual,
     Greater,
     GreaterOrEqual,
 }
@@ -38,47 +57,203 @@ impl From<&str> for OrderType {
     fn from(symbol: &str) -> Self {
         match symbol {
             "<" => OrderType::Less,
-            "<=" => OrderType::LessOrEqual,
+            "<=" | "≤" => OrderType::LessOrEqual,
             "=" => OrderType::Equal,
-            ">=" => OrderType::GreaterOrEqual,
+            "<>" | "!=" | "≠" => OrderType::NotEqual,
+            ">=" | "≥" => OrderType::GreaterOrEqual,
             ">" => OrderType::Greater,
             _ => panic!("Operator invalid!"),
         }
     }
 }
 
-fn parens<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Expression, E> {
-    delimited(space0, delimited(tag("("), expr, tag(")")), space0)(i)
+fn parens<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    delimited(
+        space0,
+        delimited(char('('), cut(expr), cut(char(')'))),
+        space0,
+    )(i)
 }
 
-fn factor<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, Expression, E> {
+fn number<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
     alt((
-        map(delimited(space0, digit1, space0), |num_str: &str| {
-            Expression::Constant(num_str.parse().unwrap())
-        }),
-        map(delimited(space0, id, space0), |id: &str| {
-            Expression::Variable(id)
+        map(
+            recognize(pair(digit1, preceded(char('.'), digit1))),
+            |num_str: &str| Expression::RealConstant(num_str.parse().unwrap()),
+        ),
+        map(
+            preceded(
+                peek(digit1),
+                cut(context(
+                    NUMBER_TOO_LARGE,
+                    verify(digit1, |num_str: &str| num_str.parse::<i32>().is_ok()),
+                )),
+            ),
+            |num_str: &str| Expression::Constant(num_str.parse().unwrap()),
+        ),
+    ))(i)
+}
+
+/// Text between single or double quotes, without the quotes. Escape sequences
+/// such as `\n` or `\'` are kept as written and replaced when the program runs.
+pub fn text<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    let quoted = |quote: char| {
+        delimited(
+            char(quote),
+            recognize(opt(escaped(
+                is_not(if quote == '\'' { "\\'" } else { "\\\"" }),
+                '\\',
+                anychar,
+            ))),
+            cut(char(quote)),
+        )
+    };
+    alt((quoted('\''), quoted('"')))(i)
+}
+
+fn integer_part<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    map(
+        delimited(
+            space0,
+            delimited(char('['), cut(expr), cut(char(']'))),
+            space0,
+        ),
+        |expr| Expression::IntegerPart(Box::new(expr)),
+    )(i)
+}
+
+fn absolute<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    map(
+        delimited(
+            space0,
+            delimited(char('|'), cut(expr), cut(char('|'))),
+            space0,
+        ),
+        |expr| Expression::Absolute(Box::new(expr)),
+    )(i)
+}
+
+/// A function name followed by its arguments, as in `max(a, b)`.
+pub fn invocation<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, (&'a str, Vec<Expression<'a>>), E> {
+    delimited(
+        space0,
+        pair(
+            id,
+            preceded(
+                pair(space0, char('(')),
+                cut(terminated(
+                    separated_list0(char(','), expr),
+                    pair(space0, char(')')),
+                )),
+            ),
+        ),
+        space0,
+    )(i)
+}
+
+fn call<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    map(invocation, |(name, arguments)| {
+        Expression::Call(name, arguments)
+    })(i)
+}
+
+/// The indices following an array name, one per dimension, written either as
+/// `[i][j]` or as `[i, j]`. They must come right after the name, since `[...]`
+/// on its own means the integer part.
+fn subscript<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Vec<Expression<'a>>, E> {
+    fold_many1(
+        delimited(
+            char('['),
+            cut(separated_list1(char(','), expr)),
+            cut(char(']')),
+        ),
+        Vec::new,
+        |mut indices, mut group| {
+            indices.append(&mut group);
+            indices
+        },
+    )(i)
+}
+
+fn index<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    map(
+        delimited(space0, pair(id, subscript), space0),
+        |(name, indices)| Expression::Index(name, indices),
+    )(i)
+}
+
+pub fn lvalue<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, LValue<'a>, E> {
+    alt((
+        map(pair(id, subscript), |(name, indices)| {
+            LValue::Index(name, indices)
         }),
-        parens,
+        map(id, LValue::Variable),
     ))(i)
 }
 
-pub fn id<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+fn factor<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    preceded(
+        space0,
+        context(
+            "o expresie",
+            alt((
+                delimited(space0, number, space0),
+                map(delimited(space0, text, space0), Expression::StringConstant),
+                call,
+                index,
+                map(delimited(space0, id, space0), |id: &str| {
+                    Expression::Variable(id)
+                }),
+                parens,
+                integer_part,
+                absolute,
+            )),
+        ),
+    )(i)
+}
+
+pub fn id<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
     if let Some(chr) = i.chars().next() {
-        if !is_alphabetic(chr as u8) {
+        if !chr.is_alphabetic() {
             return fail(i);
         }
     } else {
         return fail(i);
     }
-    take_while(|c| is_alphanumeric(c as u8) || (c as char == '_'))(i)
+    take_while(is_identifier_char)(i)
 }
 
-fn term<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Expression, E> {
+fn term<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
     let (i, init) = factor(i)?;
 
     fold_many0(
-        pair(alt((char('*'), char('/'), char('%'))), factor),
+        pair(alt((char('*'), char('/'), char('%'))), cut(factor)),
         move || init.clone(),
         |acc, (op, expr)| match op {
             '*' => Expression::Multiplication(Box::new(acc), Box::new(expr)),
@@ -88,42 +263,110 @@ fn term<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Expression, E>
     )(i)
 }
 
-fn member<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Expression, E> {
-    if let Ok((i, init)) = term::<'a, E>(i) {
-        fold_many0(
-            pair(alt((char('+'), char('-'))), term),
-            move || init.clone(),
-            |acc, (op, expr)| {
-                if op == '+' {
-                    Expression::Addition(Box::new(acc), Box::new(expr))
-                } else {
-                    Expression::Subtraction(Box::new(acc), Box::new(expr))
-                }
-            },
-        )(i)
-    } else {
-        let (i, _) = delimited(space0, char('-'), space0)(i)?;
-        map(term, |expr| Expression::Minus(Box::new(expr)))(i)
-    }
+fn member<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    let (i, init) = preceded(
+        space0,
+        context(
+            "o expresie",
+            alt((
+                term,
+                map(preceded(pair(char('-'), space0), cut(term)), |expr| {
+                    Expression::Minus(Box::new(expr))
+                }),
+            )),
+        ),
+    )(i)?;
+
+    fold_many0(
+        pair(alt((char('+'), char('-'))), cut(term)),
+        move || init.clone(),
+        |acc, (op, expr)| {
+            if op == '+' {
+                Expression::Addition(Box::new(acc), Box::new(expr))
+            } else {
+                Expression::Subtraction(Box::new(acc), Box::new(expr))
+            }
+        },
+    )(i)
 }
 
-pub fn expr<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Expression, E> {
+fn comparison<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
     let (i, init) = member(i)?;
 
     fold_many0(
         pair(
-            alt((tag("<="), tag(">="), tag("<"), tag(">"), tag("="))),
-            member,
+            alt((
+                tag("<="),
+                tag("≤"),
+                tag(">="),
+                tag("≥"),
+                tag("<>"),
+                tag("!="),
+                tag("≠"),
+                tag("<"),
+                tag(">"),
+                tag("="),
+            )),
+            cut(member),
         ),
         move || init.clone(),
         |acc, (op, expr)| Expression::Order(OrderType::from(op), Box::new(acc), Box::new(expr)),
     )(i)
 }
 
+fn operator_keyword<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    word: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    delimited(space0, keyword(word), space0)
+}
+
+fn negation<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    alt((
+        map(
+            preceded(
+                alt((operator_keyword("not"), operator_keyword("non"))),
+                cut(negation),
+            ),
+            |expr| Expression::Not(Box::new(expr)),
+        ),
+        comparison,
+    ))(i)
+}
+
+fn conjunction<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    let (i, init) = negation(i)?;
+
+    fold_many0(
+        preceded(operator_keyword("si"), cut(negation)),
+        move || init.clone(),
+        |acc, expr| Expression::And(Box::new(acc), Box::new(expr)),
+    )(i)
+}
+
+pub fn expr<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Expression<'a>, E> {
+    let (i, init) = conjunction(i)?;
+
+    fold_many0(
+        preceded(operator_keyword("sau"), cut(conjunction)),
+        move || init.clone(),
+        |acc, expr| Expression::Or(Box::new(acc), Box::new(expr)),
+    )(i)
+}
+
 #[cfg(test)]
 mod test {
-    use nom::combinator::eof;
     use super::*;
+    use nom::combinator::eof;
     use nom::error::Error;
 
     #[test]
@@ -144,6 +387,10 @@ mod test {
             factor::<Error<&str>>("  24   "),
             Ok(("", Expression::Constant(24)))
         );
+        assert_eq!(
+            factor::<Error<&str>>(" 3.25 "),
+            Ok(("", Expression::RealConstant(3.25)))
+        );
         assert_eq!(
             factor::<Error<&str>>("a"),
             Ok(("", Expression::Variable("a")))
@@ -156,6 +403,98 @@ mod test {
             factor::<Error<&str>>("variable_name  "),
             Ok(("", Expression::Variable("variable_name")))
         );
+        assert_eq!(
+            factor::<Error<&str>>("număr_maxim"),
+            Ok(("", Expression::Variable("număr_maxim")))
+        );
+        assert!(factor::<Error<&str>>("_x").is_err());
+    }
+
+    #[test]
+    fn index_test() {
+        assert_eq!(
+            factor::<Error<&str>>(" v[i + 1] "),
+            Ok((
+                "",
+                Expression::Index(
+                    "v",
+                    vec![Expression::Addition(
+                        Box::new(Expression::Variable("i")),
+                        Box::new(Expression::Constant(1))
+                    )]
+                )
+            ))
+        );
+        assert_eq!(
+            expr::<Error<&str>>("v[1]*[x]"),
+            Ok((
+                "",
+                Expression::Multiplication(
+                    Box::new(Expression::Index("v", vec![Expression::Constant(1)])),
+                    Box::new(Expression::IntegerPart(Box::new(Expression::Variable("x"))))
+                )
+            ))
+        );
+        assert_eq!(
+            lvalue::<Error<&str>>("v[2]"),
+            Ok(("", LValue::Index("v", vec![Expression::Constant(2)])))
+        );
+        assert_eq!(
+            lvalue::<Error<&str>>("v "),
+            Ok((" ", LValue::Variable("v")))
+        );
+        let matrix = Expression::Index(
+            "a",
+            vec![Expression::Variable("i"), Expression::Variable("j")],
+        );
+        assert_eq!(factor::<Error<&str>>("a[i][j]"), Ok(("", matrix.clone())));
+        assert_eq!(factor::<Error<&str>>("a[i, j]"), Ok(("", matrix)));
+        assert!(expr::<Error<&str>>("v[1").is_err());
+    }
+
+    #[test]
+    fn built_in_test() {
+        assert_eq!(
+            factor::<Error<&str>>(" [ x / 2 ] "),
+            Ok((
+                "",
+                Expression::IntegerPart(Box::new(Expression::Division(
+                    Box::new(Expression::Variable("x")),
+                    Box::new(Expression::Constant(2)),
+                )))
+            ))
+        );
+        assert_eq!(
+            factor::<Error<&str>>("|a - |b||"),
+            Ok((
+                "",
+                Expression::Absolute(Box::new(Expression::Subtraction(
+                    Box::new(Expression::Variable("a")),
+                    Box::new(Expression::Absolute(Box::new(Expression::Variable("b")))),
+                )))
+            ))
+        );
+        assert_eq!(
+            factor::<Error<&str>>("sqrt(16)"),
+            Ok(("", Expression::Call("sqrt", vec![Expression::Constant(16)])))
+        );
+        assert_eq!(
+            factor::<Error<&str>>(" max ( a, b + 1 ) "),
+            Ok((
+                "",
+                Expression::Call(
+                    "max",
+                    vec![
+                        Expression::Variable("a"),
+                        Expression::Addition(
+                            Box::new(Expression::Variable("b")),
+                            Box::new(Expression::Constant(1)),
+                        ),
+                    ]
+                )
+            ))
+        );
+        assert!(pair(factor::<Error<&str>>, eof)("[3.5").is_err());
     }
 
     #[test]
@@ -393,5 +732,73 @@ mod test {
             ))
         );
         assert!(pair(expr::<Error<&str>>, eof)("2 >").is_err());
+        assert_eq!(
+            expr::<Error<&str>>("x ≤ 6 + 3"),
+            expr::<Error<&str>>("x <= 6 + 3"),
+        );
+        assert_eq!(
+            expr::<Error<&str>>("a <> b"),
+            Ok((
+                "",
+                Expression::Order(
+                    OrderType::NotEqual,
+                    Box::new(Expression::Variable("a")),
+                    Box::new(Expression::Variable("b"))
+                )
+            ))
+        );
+    }
+
+    #[test]
+    fn logical_test() {
+        assert_eq!(
+            expr::<Error<&str>>("x > 0 si x < 10 sau not x = 20"),
+            Ok((
+                "",
+                Expression::Or(
+                    Box::new(Expression::And(
+                        Box::new(Expression::Order(
+                            OrderType::Greater,
+                            Box::new(Expression::Variable("x")),
+                            Box::new(Expression::Constant(0))
+                        )),
+                        Box::new(Expression::Order(
+                            OrderType::Less,
+                            Box::new(Expression::Variable("x")),
+                            Box::new(Expression::Constant(10))
+                        )),
+                    )),
+                    Box::new(Expression::Not(Box::new(Expression::Order(
+                        OrderType::Equal,
+                        Box::new(Expression::Variable("x")),
+                        Box::new(Expression::Constant(20))
+                    )))),
+                )
+            ))
+        );
+        assert_eq!(
+            expr::<Error<&str>>("a ≠ 1 și sigma"),
+            Ok((
+                "",
+                Expression::And(
+                    Box::new(Expression::Order(
+                        OrderType::NotEqual,
+                        Box::new(Expression::Variable("a")),
+                        Box::new(Expression::Constant(1))
+                    )),
+                    Box::new(Expression::Variable("sigma")),
+                )
+            ))
+        );
+        assert_eq!(
+            expr::<Error<&str>>("nonzero sau x"),
+            Ok((
+                "",
+                Expression::Or(
+                    Box::new(Expression::Variable("nonzero")),
+                    Box::new(Expression::Variable("x")),
+                )
+            ))
+        );
     }
 }
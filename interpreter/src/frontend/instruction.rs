@@ -1,21 +1,60 @@
-use super::expression::{expr, id, Expression};
+use super::error::INCONSISTENT_INDENTATION;
+use super::expression::{expr, id, invocation, lvalue, text, Expression, LValue};
+use super::keyword::{keyword, phrase};
+use super::whitespace::{multispace0, next_code_line, space0, space1};
+use nom::combinator::{consumed, cut, eof, fail, not, peek, success};
+use nom::error::{context, ContextError, ErrorKind, ParseError};
+use nom::multi::many0;
 use nom::Finish;
-use nom::bytes::complete::take_until;
-use nom::character::complete::{multispace0, multispace1};
-use nom::combinator::{eof, fail, success};
-use nom::error::ParseError;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{char, space0, space1},
+    character::complete::{char, line_ending},
     combinator::{map, opt},
-    multi::{many1, separated_list1},
+    multi::{many1, separated_list0, separated_list1},
     sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
-use nom::multi::many0;
+use std::cmp::Ordering;
+
+pub type Block<'a, V = &'a str> = Vec<Statement<'a, V>>;
+
+/// The source text an instruction was parsed from. Spans carry no meaning for
+/// the program itself, so they are ignored when comparing instructions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Span<'a>(pub &'a str);
 
-pub type Block<'a> = Vec<Instruction<'a>>;
+impl<'a> PartialEq for Span<'a> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<'a> Span<'a> {
+    /// The line of `program` the span starts on, if the span was taken from it.
+    pub fn line(&self, program: &str) -> Option<usize> {
+        let offset = (self.0.as_ptr() as usize).checked_sub(program.as_ptr() as usize)?;
+        if offset + self.0.len() > program.len() {
+            return None;
+        }
+        Some(program[..offset].matches('\n').count() + 1)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Statement<'a, V = &'a str> {
+    pub instruction: Instruction<'a, V>,
+    pub span: Span<'a>,
+}
+
+impl<'a, V> From<Instruction<'a, V>> for Statement<'a, V> {
+    fn from(instruction: Instruction<'a, V>) -> Self {
+        Statement {
+            instruction,
+            span: Span::default(),
+        }
+    }
+}
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum WhileType {
@@ -25,33 +64,52 @@ pub enum WhileType {
 }
 
 #[derive(PartialEq, Clone, Debug)]
-pub enum Instruction<'a> {
-    Read(Vec<&'a str>),
-    Write(Vec<Writable<'a>>),
-    Assignment(&'a str, Expression<'a>),
-    If(Expression<'a>, Block<'a>, Option<Block<'a>>),
-    While(WhileType, Expression<'a>, Block<'a>),
+pub enum Instruction<'a, V = &'a str> {
+    Read(Vec<LValue<'a, V>>),
+    /// The values and whether a newline follows them. A trailing comma, as in
+    /// `scrie x,`, leaves the cursor on the same line.
+    Write(Vec<Writable<'a, V>>, bool),
+    Assignment(LValue<'a, V>, Expression<'a, V>),
+    If(Expression<'a, V>, Block<'a, V>, Option<Block<'a, V>>),
+    While(WhileType, Expression<'a, V>, Block<'a, V>),
     For {
-        variable: &'a str,
-        start_expr: Expression<'a>,
-        end_expr: Expression<'a>,
-        step: Expression<'a>,
-        block: Block<'a>,
+        variable: V,
+        start_expr: Expression<'a, V>,
+        end_expr: Expression<'a, V>,
+        step: Expression<'a, V>,
+        block: Block<'a, V>,
     },
+    Call(&'a str, Vec<Expression<'a, V>>),
+    Return(Option<Expression<'a, V>>),
+    Subprogram(Subprogram<'a, V>),
+}
+
+/// A `subprogram` or `functie` definition. Functions return a value with
+/// `returneaza`, while procedures simply reach the end of their block.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Subprogram<'a, V = &'a str> {
+    pub name: &'a str,
+    pub parameters: Vec<V>,
+    pub block: Block<'a, V>,
 }
 
 #[derive(PartialEq, Clone, Debug)]
-pub enum Writable<'a> {
-    Expression(Expression<'a>),
+pub enum Writable<'a, V = &'a str> {
+    Expression(Expression<'a, V>),
     String(&'a str),
 }
 
-fn read<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Instruction, E> {
+fn read<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Instruction<'a>, E> {
     map(
         preceded(
-            delimited(space0, tag("citeste"), space1),
+            delimited(space0, keyword("citeste"), space1),
             terminated(
-                separated_list1(delimited(space0, char(','), space0), id),
+                separated_list1(
+                    delimited(space0, char(','), space0),
+                    cut(context("un identificator", lvalue)),
+                ),
                 space0,
             ),
         ),
@@ -59,185 +117,416 @@ fn read<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Instruction, E>
     )(i)
 }
 
-fn write<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Instruction, E> {
+fn write<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Instruction<'a>, E> {
     map(
         preceded(
-            delimited(space0, tag("scrie"), space1),
-            separated_list1(
-                delimited(space0, char(','), space0),
-                alt((map(expr, Writable::Expression), string)),
+            delimited(space0, keyword("scrie"), space1),
+            pair(
+                separated_list1(
+                    delimited(space0, char(','), space0),
+                    preceded(
+                        not(peek(alt((line_ending, eof)))),
+                        cut(alt((
+                            terminated(string, peek(alt((tag(","), line_ending, eof)))),
+                            map(expr, Writable::Expression),
+                        ))),
+                    ),
+                ),
+                opt(delimited(space0, char(','), space0)),
             ),
         ),
-        Instruction::Write,
+        |(writables, trailing_comma)| Instruction::Write(writables, trailing_comma.is_none()),
     )(i)
 }
 
-fn string<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Writable, E> {
+fn string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Writable<'a>, E> {
+    map(delimited(space0, text, space0), Writable::String)(i)
+}
+
+fn arrow<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    delimited(space0, context("<-", alt((tag("<-"), tag("←")))), space0)(i)
+}
+
+fn assignment<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Instruction<'a>, E> {
     map(
-        delimited(
-            pair(space0, char('\'')),
-            take_until("'"),
-            pair(char('\''), space0),
-        ),
-        Writable::String,
+        pair(terminated(preceded(space0, lvalue), arrow), cut(expr)),
+        |(target, expr)| Instruction::Assignment(target, expr),
     )(i)
 }
 
-fn assignment<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&str, Instruction, E> {
+fn call<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Instruction<'a>, E> {
+    map(invocation, |(name, arguments)| {
+        Instruction::Call(name, arguments)
+    })(i)
+}
+
+fn return_instr<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, Instruction<'a>, E> {
     map(
-        pair(
-            terminated(preceded(space0, id), delimited(space0, tag("<-"), space0)),
-            expr,
-        ),
-        |(id, expr)| Instruction::Assignment(id, expr),
+        delimited(pair(space0, keyword("returneaza")), opt(expr), space0),
+        Instruction::Return,
     )(i)
 }
 
-fn instruction<'a, E: ParseError<&'a str>>(
-    indent: usize,
+/// Subprograms can only be defined at the top level of the program.
+fn subprogram<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
+) -> impl FnMut(&'a str) -> IResult<&str, Instruction<'a>, E> {
+    move |i: &'a str| {
+        if level.depth > 0 {
+            return fail(i);
+        }
+        map(
+            tuple((
+                preceded(
+                    pair(alt((keyword("subprogram"), keyword("functie"))), space1),
+                    cut(context("un identificator", id)),
+                ),
+                preceded(
+                    pair(space0, cut(char('('))),
+                    cut(terminated(
+                        separated_list0(char(','), delimited(space0, id, space0)),
+                        char(')'),
+                    )),
+                ),
+                terminated(
+                    preceded(space0, nested_block(level)),
+                    alt((end(level, "subprogram"), end(level, "functie"))),
+                ),
+            )),
+            |(name, parameters, block)| {
+                Instruction::Subprogram(Subprogram {
+                    name,
+                    parameters,
+                    block,
+                })
+            },
+        )(i)
+    }
+}
+
+fn instruction<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl Fn(&'a str) -> IResult<&str, Instruction<'a>, E> {
     move |i: &'a str| {
-        alt((
-            read,
-            write,
-            assignment,
-            if_instr(indent),
-            while_instr(indent),
-            do_while_instr(indent),
-            repeat_instr(indent),
-            for_instr(indent),
-        ))(i)
+        context(
+            "o instructiune",
+            alt((
+                read,
+                write,
+                return_instr,
+                subprogram(level),
+                assignment,
+                if_instr(level),
+                while_instr(level),
+                do_while_instr(level),
+                repeat_instr(level),
+                for_instr(level),
+                // After the instructions that start with a keyword, so that
+                // `daca (x < y) atunci` is not taken for a call.
+                call,
+            )),
+        )(i)
     }
 }
 
-fn if_instr<'a, E: ParseError<&'a str>>(
-    indent: usize,
+fn statement<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Statement<'a>, E> {
+    map(consumed(instruction(level)), |(text, instruction)| {
+        Statement {
+            instruction,
+            span: Span(text.trim()),
+        }
+    })
+}
+
+fn if_instr<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl FnMut(&'a str) -> IResult<&str, Instruction<'a>, E> {
     map(
         tuple((
-            preceded(terminated(tag("daca"), space0), expr),
-            preceded(terminated(tag("atunci"), space0), block(Some(indent + 1))),
-            opt(preceded(
-                terminated(pair(indentation(indent), tag("altfel")), space0),
-                block(Some(indent + 1)),
-            )),
+            preceded(terminated(keyword("daca"), space0), expr),
+            preceded(terminated(keyword("atunci"), space0), nested_block(level)),
+            terminated(
+                opt(preceded(
+                    terminated(pair(indentation(level), keyword("altfel")), space0),
+                    nested_block(level),
+                )),
+                end(level, "daca"),
+            ),
         )),
         |(expr, if_block, else_block)| Instruction::If(expr, if_block, else_block),
     )
 }
 
-fn while_instr<'a, E: ParseError<&'a str>>(
-    indent: usize,
+fn while_instr<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl FnMut(&'a str) -> IResult<&str, Instruction<'a>, E> {
     map(
         pair(
-            preceded(tuple((tag("cat"), space1, tag("timp"), space1)), expr),
-            preceded(terminated(tag("executa"), space0), block(Some(indent + 1))),
+            preceded(phrase("cat timp"), expr),
+            delimited(
+                terminated(keyword("executa"), space0),
+                nested_block(level),
+                end(level, "cat timp"),
+            ),
         ),
         |(expr, block)| Instruction::While(WhileType::While, expr, block),
     )
 }
 
-fn do_while_instr<'a, E: ParseError<&'a str>>(
-    indent: usize,
+fn do_while_instr<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl FnMut(&'a str) -> IResult<&str, Instruction<'a>, E> {
     map(
         pair(
-            preceded(
-                terminated(tag("executa"), space0),
-                block(Some(indent + 1))
-            ),
-            preceded(
-                tuple((indentation(indent), tag("cat"), space1, tag("timp"), space1)),
-                expr,
-            ),
+            preceded(terminated(keyword("executa"), space0), nested_block(level)),
+            preceded(pair(indentation(level), phrase("cat timp")), expr),
         ),
         |(block, expr)| Instruction::While(WhileType::DoWhile, expr, block),
     )
 }
 
-fn repeat_instr<'a, E: ParseError<&'a str>>(
-    indent: usize,
+fn repeat_instr<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl FnMut(&'a str) -> IResult<&str, Instruction<'a>, E> {
     map(
         pair(
-            preceded(
-                terminated(tag("repeta"), space0),
-                block(Some(indent + 1))
-            ),
-            preceded(
-                tuple((
-                    indentation(indent),
-                    tag("pana"),
-                    space1,
-                    tag("cand"),
-                    space1,
-                )),
-                expr,
-            ),
+            preceded(terminated(keyword("repeta"), space0), nested_block(level)),
+            preceded(pair(indentation(level), phrase("pana cand")), expr),
         ),
         |(block, expr)| Instruction::While(WhileType::Repeat, expr, block),
     )
 }
 
-fn for_instr<'a, E: ParseError<&'a str>>(
-    indent: usize,
+fn for_instr<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl FnMut(&'a str) -> IResult<&str, Instruction<'a>, E> {
     map(
         tuple((
-            preceded(tuple((tag("pentru"), space1)), assignment),
+            preceded(
+                tuple((keyword("pentru"), space1)),
+                pair(terminated(id, arrow), cut(expr)),
+            ),
             preceded(delimited(space0, char(','), space0), expr),
             opt(preceded(delimited(space0, char(','), space0), expr)),
-            preceded(terminated(tag("executa"), space0), block(Some(indent + 1))),
+            delimited(
+                terminated(keyword("executa"), space0),
+                nested_block(level),
+                end(level, "pentru"),
+            ),
         )),
-        |(assignment, end_expr, step, block)| {
-            let step = step.unwrap_or(Expression::Constant(1));
-            if let Instruction::Assignment(variable, start_expr) = assignment {
-                Instruction::For {
-                    variable,
-                    start_expr,
-                    end_expr,
-                    step,
-                    block,
-                }
-            } else {
-                panic!("Intializare invalida in structura repetitiva!")
-            }
+        |((variable, start_expr), end_expr, step, block)| Instruction::For {
+            variable,
+            start_expr,
+            end_expr,
+            step: step.unwrap_or(Expression::Constant(1)),
+            block,
         },
     )
 }
 
-fn block<'a, E: ParseError<&'a str>>(
-    maybe_indent: Option<usize>,
+/// How the end of a block is marked.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Layout {
+    /// A block holds the lines indented below the instruction it belongs to.
+    Indentation,
+    /// A block runs until a closing line such as `sfarsit daca` or `sf daca`,
+    /// and indentation does not matter.
+    Terminators,
+}
+
+/// Where a block is parsed: how blocks are closed, how many blocks it is
+/// nested in and, for indented blocks, the column its lines start at.
+#[derive(Clone, Copy, Debug)]
+struct Level {
+    layout: Layout,
+    tab_width: usize,
+    depth: usize,
+    column: usize,
+}
+
+impl Level {
+    fn nested(self, column: usize) -> Self {
+        Level {
+            depth: self.depth + 1,
+            column,
+            ..self
+        }
+    }
+
+    /// The column a line indented by `indentation` starts at. A tab moves to
+    /// the next multiple of the tab width.
+    fn column_of(&self, indentation: &str) -> usize {
+        indentation.chars().fold(0, |column, c| {
+            if c == '\t' {
+                (column / self.tab_width + 1) * self.tab_width
+            } else {
+                column + 1
+            }
+        })
+    }
+}
+
+/// A line that closes a block in the `Terminators` layout. A do-while loop is
+/// closed by its `cat timp` condition, which can also start a while loop.
+fn closing_line<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    alt((
+        keyword("sfarsit"),
+        keyword("sf"),
+        keyword("altfel"),
+        phrase("pana cand"),
+        phrase("cat timp"),
+    ))(i)
+}
+
+/// The `sfarsit` or `sf` line closing a block, as in `sfarsit cat timp`.
+/// Blocks closed by indentation have no such line.
+fn end<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
+    words: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (), E> {
+    move |i: &'a str| match level.layout {
+        Layout::Indentation => success(())(i),
+        Layout::Terminators => map(
+            preceded(
+                indentation(level),
+                cut(tuple((
+                    alt((keyword("sfarsit"), keyword("sf"))),
+                    space1,
+                    phrase(words),
+                ))),
+            ),
+            |_| (),
+        )(i),
+    }
+}
+
+/// The lines of a block at `level`, each holding one statement.
+fn block<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl FnMut(&'a str) -> IResult<&'a str, Block<'a>, E> {
     move |i: &'a str| {
-        if let Some(indent) = maybe_indent {
-            many1(preceded(indentation(indent), instruction(indent)))(i)
+        let line = |i: &'a str| match level.layout {
+            Layout::Indentation => cut(statement(level))(i),
+            Layout::Terminators => alt((
+                preceded(not(closing_line), cut(statement(level))),
+                statement(level),
+            ))(i),
+        };
+        if level.depth > 0 {
+            many1(preceded(indentation(level), line))(i)
         } else {
-            map(pair(
-                preceded(multispace0, instruction(0)),
-                many0(preceded(indentation(0), instruction(0)))
-            ), |(first, mut rest)| {
-                rest.insert(0, first);
-                rest
-            })(i)
+            map(
+                pair(
+                    preceded(multispace0, statement(level)),
+                    many0(preceded(indentation(level), line)),
+                ),
+                |(first, mut rest)| {
+                    rest.insert(0, first);
+                    rest
+                },
+            )(i)
         }
     }
 }
 
-pub fn program<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<(&str, Block), E> {
-    terminated(block(None), pair(multispace0, eof))(i).finish()
+/// The block of an instruction at `parent`. When blocks are indented, the
+/// first line sets how deep the whole block is, which must be deeper than the
+/// instruction itself.
+fn nested_block<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    parent: Level,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Block<'a>, E> {
+    move |i: &'a str| {
+        let column = match (parent.layout, next_code_line::<E>(i)) {
+            (Layout::Terminators, _) => parent.column,
+            (Layout::Indentation, Ok((_, spaces))) if parent.column_of(spaces) > parent.column => {
+                parent.column_of(spaces)
+            }
+            _ => return context("un bloc indentat", fail)(i),
+        };
+        context("un bloc indentat", block(parent.nested(column)))(i)
+    }
+}
+
+/// Guesses the layout of a program: it uses terminators if any line starts
+/// with `sfarsit` or `sf` followed by another word.
+pub fn detect_layout(source: &str) -> Layout {
+    let closes_block = |line: &str| {
+        let closing: IResult<_, _, nom::error::Error<_>> =
+            pair(alt((keyword("sfarsit"), keyword("sf"))), pair(space1, id))(line.trim_start());
+        closing.is_ok()
+    };
+    if source.lines().any(closes_block) {
+        Layout::Terminators
+    } else {
+        Layout::Indentation
+    }
 }
 
-fn indentation<'a, E: ParseError<&'a str>>(
-    indent: usize,
+/// Tabs count as this many spaces, unless another width is given.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// Parses a program with the layout it appears to use and the default tab
+/// width.
+#[cfg(test)]
+pub fn program<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<(&'a str, Block<'a>), E> {
+    program_with(i, detect_layout(i), DEFAULT_TAB_WIDTH)
+}
+
+pub fn program_with<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    layout: Layout,
+    tab_width: usize,
+) -> Result<(&'a str, Block<'a>), E> {
+    let level = Level {
+        layout,
+        tab_width: tab_width.max(1),
+        depth: 0,
+        column: 0,
+    };
+    terminated(
+        block(level),
+        context("sfarsitul liniei", pair(multispace0, eof)),
+    )(i)
+    .finish()
+}
+
+/// Moves to the next line of code, which must be indented as deep as `level`
+/// unless blocks are closed by terminators. A shallower line ends the block,
+/// while a deeper one does not line up with any block and is an error.
+fn indentation<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    level: Level,
 ) -> impl FnMut(&'a str) -> IResult<&str, (), E> {
     move |i: &'a str| {
-        let (i, spaces) = multispace1(i)?;
-        if spaces.ends_with(&format!("\n{}", " ".repeat(2 * indent))) {
-            success(())(i)
-        } else {
-            fail(i)
+        let (rest, spaces) = next_code_line(i)?;
+        if level.layout == Layout::Terminators {
+            return success(())(rest);
+        }
+        match level.column_of(spaces).cmp(&level.column) {
+            Ordering::Equal => success(())(rest),
+            Ordering::Less => fail(i),
+            Ordering::Greater => Err(nom::Err::Failure(E::add_context(
+                rest,
+                INCONSISTENT_INDENTATION,
+                E::from_error_kind(rest, ErrorKind::Verify),
+            ))),
         }
     }
 }
@@ -247,19 +536,50 @@ mod test {
     use super::*;
     use nom::error::Error;
 
+    const TOP: Level = Level {
+        layout: Layout::Indentation,
+        tab_width: DEFAULT_TAB_WIDTH,
+        depth: 0,
+        column: 0,
+    };
+
+    fn statements(instructions: Vec<Instruction>) -> Block {
+        instructions.into_iter().map(Statement::from).collect()
+    }
+
     #[test]
     fn read_test() {
         assert_eq!(
             read::<Error<&str>>("citeste a  "),
-            Ok(("", Instruction::Read(vec!["a"])))
+            Ok(("", Instruction::Read(vec![LValue::Variable("a")])))
         );
         assert_eq!(
             read::<Error<&str>>("  citeste   a  , b   "),
-            Ok(("", Instruction::Read(vec!["a", "b"])))
+            Ok((
+                "",
+                Instruction::Read(vec![LValue::Variable("a"), LValue::Variable("b")])
+            ))
         );
         assert_eq!(
             read::<Error<&str>>(" citeste   a34  , b123,a_34   "),
-            Ok(("", Instruction::Read(vec!["a34", "b123", "a_34"])))
+            Ok((
+                "",
+                Instruction::Read(vec![
+                    LValue::Variable("a34"),
+                    LValue::Variable("b123"),
+                    LValue::Variable("a_34")
+                ])
+            ))
+        );
+        assert_eq!(
+            read::<Error<&str>>("citeste n, v[i]"),
+            Ok((
+                "",
+                Instruction::Read(vec![
+                    LValue::Variable("n"),
+                    LValue::Index("v", vec![Expression::Variable("i")])
+                ])
+            ))
         );
     }
 
@@ -269,80 +589,161 @@ mod test {
             write::<Error<&str>>("scrie 1"),
             Ok((
                 "",
-                Instruction::Write(vec![Writable::Expression(Expression::Constant(1))])
+                Instruction::Write(vec![Writable::Expression(Expression::Constant(1))], true)
             ))
         );
         assert_eq!(
             write::<Error<&str>>("  scrie  6 + 5  "),
             Ok((
                 "",
-                Instruction::Write(vec![Writable::Expression(Expression::Addition(
-                    Box::new(Expression::Constant(6)),
-                    Box::new(Expression::Constant(5)),
-                ))])
+                Instruction::Write(
+                    vec![Writable::Expression(Expression::Addition(
+                        Box::new(Expression::Constant(6)),
+                        Box::new(Expression::Constant(5)),
+                    ))],
+                    true
+                )
             ))
         );
         assert_eq!(
             write::<Error<&str>>(" scrie (a + b) - 3"),
             Ok((
                 "",
-                Instruction::Write(vec![Writable::Expression(Expression::Subtraction(
-                    Box::new(Expression::Addition(
-                        Box::new(Expression::Variable("a")),
-                        Box::new(Expression::Variable("b")),
-                    )),
-                    Box::new(Expression::Constant(3)),
-                ))])
+                Instruction::Write(
+                    vec![Writable::Expression(Expression::Subtraction(
+                        Box::new(Expression::Addition(
+                            Box::new(Expression::Variable("a")),
+                            Box::new(Expression::Variable("b")),
+                        )),
+                        Box::new(Expression::Constant(3)),
+                    ))],
+                    true
+                )
             ))
         );
         assert_eq!(
             write::<Error<&str>>("scrie 3 * 4 + var"),
             Ok((
                 "",
-                Instruction::Write(vec![Writable::Expression(Expression::Addition(
-                    Box::new(Expression::Multiplication(
-                        Box::new(Expression::Constant(3)),
-                        Box::new(Expression::Constant(4)),
-                    )),
-                    Box::new(Expression::Variable("var")),
-                ))])
+                Instruction::Write(
+                    vec![Writable::Expression(Expression::Addition(
+                        Box::new(Expression::Multiplication(
+                            Box::new(Expression::Constant(3)),
+                            Box::new(Expression::Constant(4)),
+                        )),
+                        Box::new(Expression::Variable("var")),
+                    ))],
+                    true
+                )
             ))
         );
         assert_eq!(
             write::<Error<&str>>("scrie 'it is interesting'"),
             Ok((
                 "",
-                Instruction::Write(vec![Writable::String("it is interesting")])
+                Instruction::Write(vec![Writable::String("it is interesting")], true)
             ))
         );
         assert_eq!(
             write::<Error<&str>>("scrie 'henlo'"),
-            Ok(("", Instruction::Write(vec![Writable::String("henlo")])))
+            Ok((
+                "",
+                Instruction::Write(vec![Writable::String("henlo")], true)
+            ))
         );
         assert_eq!(
             write::<Error<&str>>("scrie x, 'henlo'"),
             Ok((
                 "",
-                Instruction::Write(vec![
-                    Writable::Expression(Expression::Variable("x")),
-                    Writable::String("henlo")
-                ])
+                Instruction::Write(
+                    vec![
+                        Writable::Expression(Expression::Variable("x")),
+                        Writable::String("henlo")
+                    ],
+                    true
+                )
             ))
         );
+        assert_eq!(
+            write::<Error<&str>>("scrie 'a' + s, 'b'\n"),
+            Ok((
+                "\n",
+                Instruction::Write(
+                    vec![
+                        Writable::Expression(Expression::Addition(
+                            Box::new(Expression::StringConstant("a")),
+                            Box::new(Expression::Variable("s"))
+                        )),
+                        Writable::String("b")
+                    ],
+                    true
+                )
+            ))
+        );
+        assert_eq!(
+            write::<Error<&str>>("scrie v[i], ' ',\n"),
+            Ok((
+                "\n",
+                Instruction::Write(
+                    vec![
+                        Writable::Expression(Expression::Index(
+                            "v",
+                            vec![Expression::Variable("i")]
+                        )),
+                        Writable::String(" ")
+                    ],
+                    false
+                )
+            ))
+        );
+        assert_eq!(
+            write::<Error<&str>>(r#"scrie "it's", 'a\'b\n'"#),
+            Ok((
+                "",
+                Instruction::Write(
+                    vec![Writable::String("it's"), Writable::String(r"a\'b\n")],
+                    true
+                )
+            ))
+        );
+        assert_eq!(
+            write::<Error<&str>>("scrie ''"),
+            Ok(("", Instruction::Write(vec![Writable::String("")], true)))
+        );
     }
 
     #[test]
     fn assignment_test() {
+        assert_eq!(
+            assignment::<Error<&str>>("v[1] <- 0"),
+            Ok((
+                "",
+                Instruction::Assignment(
+                    LValue::Index("v", vec![Expression::Constant(1)]),
+                    Expression::Constant(0)
+                )
+            ))
+        );
         assert_eq!(
             assignment::<Error<&str>>("x <- 1"),
-            Ok(("", Instruction::Assignment("x", Expression::Constant(1))))
+            Ok((
+                "",
+                Instruction::Assignment(LValue::Variable("x"), Expression::Constant(1))
+            ))
+        );
+        assert_eq!(
+            assignment::<Error<&str>>("x ← 1"),
+            Ok((
+                "",
+                Instruction::Assignment(LValue::Variable("x"), Expression::Constant(1))
+            ))
         );
         assert_eq!(
             assignment::<Error<&str>>(" var12<-6 + 5 "),
             Ok((
                 "",
                 Instruction::Assignment(
-                    "var12",
+                    LValue::Variable("var12"),
                     Expression::Addition(
                         Box::new(Expression::Constant(6)),
                         Box::new(Expression::Constant(5)),
@@ -355,7 +756,7 @@ mod test {
             Ok((
                 "",
                 Instruction::Assignment(
-                    "v",
+                    LValue::Variable("v"),
                     Expression::Addition(
                         Box::new(Expression::Constant(5)),
                         Box::new(Expression::Multiplication(
@@ -371,20 +772,21 @@ mod test {
     #[test]
     fn if_test() {
         assert_eq!(
-            if_instr::<Error<&str>>(0)("daca 1 atunci \n  scrie 15"),
+            if_instr::<Error<&str>>(TOP)("daca 1 atunci \n  scrie 15"),
             Ok((
                 "",
                 Instruction::If(
                     Expression::Constant(1),
-                    vec![Instruction::Write(vec![Writable::Expression(
-                        Expression::Constant(15)
-                    )])],
+                    statements(vec![Instruction::Write(
+                        vec![Writable::Expression(Expression::Constant(15))],
+                        true
+                    )]),
                     None,
                 )
             ))
         );
         assert_eq!(
-            if_instr::<Error<&str>>(0)("daca 5 + 5 atunci \n  scrie 10\n  scrie 16"),
+            if_instr::<Error<&str>>(TOP)("daca 5 + 5 atunci \n  scrie 10\n  scrie 16"),
             Ok((
                 "",
                 Instruction::If(
@@ -392,73 +794,96 @@ mod test {
                         Box::new(Expression::Constant(5)),
                         Box::new(Expression::Constant(5)),
                     ),
-                    vec![
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(10))]),
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(16))]),
-                    ],
+                    statements(vec![
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(10))],
+                            true
+                        ),
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(16))],
+                            true
+                        ),
+                    ]),
                     None,
                 )
             ))
         );
         assert_eq!(
-            if_instr::<Error<&str>>(0)("daca 1 atunci\n  daca 2 atunci\n    scrie 5\n    scrie 6"),
+            if_instr::<Error<&str>>(TOP)(
+                "daca 1 atunci\n  daca 2 atunci\n    scrie 5\n    scrie 6"
+            ),
             Ok((
                 "",
                 Instruction::If(
                     Expression::Constant(1),
-                    vec![Instruction::If(
+                    statements(vec![Instruction::If(
                         Expression::Constant(2),
-                        vec![
-                            Instruction::Write(vec![Writable::Expression(Expression::Constant(5))]),
-                            Instruction::Write(vec![Writable::Expression(Expression::Constant(6))]),
-                        ],
+                        statements(vec![
+                            Instruction::Write(
+                                vec![Writable::Expression(Expression::Constant(5))],
+                                true
+                            ),
+                            Instruction::Write(
+                                vec![Writable::Expression(Expression::Constant(6))],
+                                true
+                            ),
+                        ]),
                         None,
-                    )],
+                    )]),
                     None,
                 )
             ))
         );
         assert_eq!(
-            if_instr::<Error<&str>>(0)("daca 1 atunci\n  daca 2 atunci\n    scrie 5\n  scrie 6"),
+            if_instr::<Error<&str>>(TOP)("daca 1 atunci\n  daca 2 atunci\n    scrie 5\n  scrie 6"),
             Ok((
                 "",
                 Instruction::If(
                     Expression::Constant(1),
-                    vec![
+                    statements(vec![
                         Instruction::If(
                             Expression::Constant(2),
-                            vec![Instruction::Write(vec![Writable::Expression(
-                                Expression::Constant(5)
-                            )])],
+                            statements(vec![Instruction::Write(
+                                vec![Writable::Expression(Expression::Constant(5))],
+                                true
+                            )]),
                             None,
                         ),
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(6))]),
-                    ],
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(6))],
+                            true
+                        ),
+                    ]),
                     None,
                 )
             ))
         );
         assert_eq!(
-            if_instr::<Error<&str>>(0)(
+            if_instr::<Error<&str>>(TOP)(
                 "daca 1 atunci\n  daca 2 atunci\n    scrie 5\n  scrie 6\naltfel\n  scrie 1"
             ),
             Ok((
                 "",
                 Instruction::If(
                     Expression::Constant(1),
-                    vec![
+                    statements(vec![
                         Instruction::If(
                             Expression::Constant(2),
-                            vec![Instruction::Write(vec![Writable::Expression(
-                                Expression::Constant(5)
-                            )])],
+                            statements(vec![Instruction::Write(
+                                vec![Writable::Expression(Expression::Constant(5))],
+                                true
+                            )]),
                             None,
                         ),
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(6))]),
-                    ],
-                    Some(vec![Instruction::Write(vec![Writable::Expression(
-                        Expression::Constant(1)
-                    )])]),
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(6))],
+                            true
+                        ),
+                    ]),
+                    Some(statements(vec![Instruction::Write(
+                        vec![Writable::Expression(Expression::Constant(1))],
+                        true
+                    )])),
                 )
             ))
         );
@@ -467,33 +892,42 @@ mod test {
     #[test]
     fn while_test() {
         assert_eq!(
-            while_instr::<Error<&str>>(0)("cat timp 1 executa\n  scrie 2\n  scrie 4"),
+            while_instr::<Error<&str>>(TOP)("cat timp 1 executa\n  scrie 2\n  scrie 4"),
             Ok((
                 "",
                 Instruction::While(
                     WhileType::While,
                     Expression::Constant(1),
-                    vec![
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(2))]),
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(4))]),
-                    ],
+                    statements(vec![
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(2))],
+                            true
+                        ),
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(4))],
+                            true
+                        ),
+                    ]),
                 )
             ))
         );
         assert_eq!(
-            while_instr::<Error<&str>>(0)("cat timp 1 executa\n  cat timp 2 executa\n    scrie 1"),
+            while_instr::<Error<&str>>(TOP)(
+                "cat timp 1 executa\n  cat timp 2 executa\n    scrie 1"
+            ),
             Ok((
                 "",
                 Instruction::While(
                     WhileType::While,
                     Expression::Constant(1),
-                    vec![Instruction::While(
+                    statements(vec![Instruction::While(
                         WhileType::While,
                         Expression::Constant(2),
-                        vec![Instruction::Write(vec![Writable::Expression(
-                            Expression::Constant(1)
-                        )])],
-                    )],
+                        statements(vec![Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(1))],
+                            true
+                        )]),
+                    )]),
                 )
             ))
         );
@@ -502,21 +936,27 @@ mod test {
     #[test]
     fn do_while_test() {
         assert_eq!(
-            do_while_instr::<Error<&str>>(0)("executa\n  scrie x\n  scrie 2\ncat timp 1"),
+            do_while_instr::<Error<&str>>(TOP)("executa\n  scrie x\n  scrie 2\ncat timp 1"),
             Ok((
                 "",
                 Instruction::While(
                     WhileType::DoWhile,
                     Expression::Constant(1),
-                    vec![
-                        Instruction::Write(vec![Writable::Expression(Expression::Variable("x"))]),
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(2))]),
-                    ],
+                    statements(vec![
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Variable("x"))],
+                            true
+                        ),
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(2))],
+                            true
+                        ),
+                    ]),
                 )
             ))
         );
         assert_eq!(
-            do_while_instr::<Error<&str>>(0)(
+            do_while_instr::<Error<&str>>(TOP)(
                 "executa\n  executa\n    scrie x\n  cat timp m\ncat timp 1"
             ),
             Ok((
@@ -524,13 +964,14 @@ mod test {
                 Instruction::While(
                     WhileType::DoWhile,
                     Expression::Constant(1),
-                    vec![Instruction::While(
+                    statements(vec![Instruction::While(
                         WhileType::DoWhile,
                         Expression::Variable("m"),
-                        vec![Instruction::Write(vec![Writable::Expression(
-                            Expression::Variable("x")
-                        )])],
-                    )],
+                        statements(vec![Instruction::Write(
+                            vec![Writable::Expression(Expression::Variable("x"))],
+                            true
+                        )]),
+                    )]),
                 )
             ))
         );
@@ -539,21 +980,27 @@ mod test {
     #[test]
     fn repeat_test() {
         assert_eq!(
-            repeat_instr::<Error<&str>>(0)("repeta\n  scrie x\n  scrie 5\npana cand 1"),
+            repeat_instr::<Error<&str>>(TOP)("repeta\n  scrie x\n  scrie 5\npana cand 1"),
             Ok((
                 "",
                 Instruction::While(
                     WhileType::Repeat,
                     Expression::Constant(1),
-                    vec![
-                        Instruction::Write(vec![Writable::Expression(Expression::Variable("x"))]),
-                        Instruction::Write(vec![Writable::Expression(Expression::Constant(5))]),
-                    ],
+                    statements(vec![
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Variable("x"))],
+                            true
+                        ),
+                        Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(5))],
+                            true
+                        ),
+                    ]),
                 )
             ))
         );
         assert_eq!(
-            repeat_instr::<Error<&str>>(0)(
+            repeat_instr::<Error<&str>>(TOP)(
                 "repeta\n  repeta\n    scrie 13\n  pana cand m\npana cand 1"
             ),
             Ok((
@@ -561,13 +1008,14 @@ mod test {
                 Instruction::While(
                     WhileType::Repeat,
                     Expression::Constant(1),
-                    vec![Instruction::While(
+                    statements(vec![Instruction::While(
                         WhileType::Repeat,
                         Expression::Variable("m"),
-                        vec![Instruction::Write(vec![Writable::Expression(
-                            Expression::Constant(13)
-                        )])],
-                    )],
+                        statements(vec![Instruction::Write(
+                            vec![Writable::Expression(Expression::Constant(13))],
+                            true
+                        )]),
+                    )]),
                 )
             ))
         );
@@ -576,7 +1024,7 @@ mod test {
     #[test]
     fn for_test() {
         assert_eq!(
-            for_instr::<Error<&str>>(0)("pentru x<-1, 2 executa\n  scrie x"),
+            for_instr::<Error<&str>>(TOP)("pentru x<-1, 2 executa\n  scrie x"),
             Ok((
                 "",
                 Instruction::For {
@@ -584,14 +1032,15 @@ mod test {
                     start_expr: Expression::Constant(1),
                     end_expr: Expression::Constant(2),
                     step: Expression::Constant(1),
-                    block: vec![Instruction::Write(vec![Writable::Expression(
-                        Expression::Variable("x")
-                    )])],
+                    block: statements(vec![Instruction::Write(
+                        vec![Writable::Expression(Expression::Variable("x"))],
+                        true
+                    )]),
                 }
             ))
         );
         assert_eq!(
-            for_instr::<Error<&str>>(0)("pentru var<- 0, 5  , 2   executa\n  scrie var"),
+            for_instr::<Error<&str>>(TOP)("pentru var<- 0, 5  , 2   executa\n  scrie var"),
             Ok((
                 "",
                 Instruction::For {
@@ -599,11 +1048,137 @@ mod test {
                     start_expr: Expression::Constant(0),
                     end_expr: Expression::Constant(5),
                     step: Expression::Constant(2),
-                    block: vec![Instruction::Write(vec![Writable::Expression(
-                        Expression::Variable("var")
-                    )])],
+                    block: statements(vec![Instruction::Write(
+                        vec![Writable::Expression(Expression::Variable("var"))],
+                        true
+                    )]),
                 }
             ))
         );
     }
+
+    #[test]
+    fn span_test() {
+        let source = "x <- 1\ndaca x atunci\n  scrie x\n  x <- 2  \n";
+        let (_, program) = program::<Error<&str>>(source).unwrap();
+        assert_eq!(program[0].span.0, "x <- 1");
+        assert_eq!(program[0].span.line(source), Some(1));
+        assert_eq!(program[1].span.0, "daca x atunci\n  scrie x\n  x <- 2");
+        assert_eq!(program[1].span.line(source), Some(2));
+        if let Instruction::If(_, block, _) = &program[1].instruction {
+            assert_eq!(block[1].span.0, "x <- 2");
+            assert_eq!(block[1].span.line(source), Some(4));
+        } else {
+            panic!("Expected an if instruction");
+        }
+        assert_eq!(Span("x <- 1").line(source), None);
+    }
+
+    #[test]
+    fn subprogram_test() {
+        assert_eq!(
+            program::<Error<&str>>("functie f(a, b)\n  returneaza a + b\nscrie f(1, 2)\nf()"),
+            Ok((
+                "",
+                statements(vec![
+                    Instruction::Subprogram(Subprogram {
+                        name: "f",
+                        parameters: vec!["a", "b"],
+                        block: statements(vec![Instruction::Return(Some(Expression::Addition(
+                            Box::new(Expression::Variable("a")),
+                            Box::new(Expression::Variable("b"))
+                        )))])
+                    }),
+                    Instruction::Write(
+                        vec![Writable::Expression(Expression::Call(
+                            "f",
+                            vec![Expression::Constant(1), Expression::Constant(2)]
+                        ))],
+                        true
+                    ),
+                    Instruction::Call("f", vec![]),
+                ])
+            ))
+        );
+        assert_eq!(
+            program::<Error<&str>>("subprogram p()\n  returneaza\n"),
+            Ok((
+                "",
+                statements(vec![Instruction::Subprogram(Subprogram {
+                    name: "p",
+                    parameters: vec![],
+                    block: statements(vec![Instruction::Return(None)])
+                })])
+            ))
+        );
+        assert!(program::<Error<&str>>("daca 1 atunci\n  functie f()\n    scrie 1").is_err());
+    }
+
+    #[test]
+    fn terminators_test() {
+        let indented = "citeste n\npentru i <- 1, n executa\n  daca i % 2 = 0 atunci\n    scrie i\n  altfel\n    cat timp n > 0 executa\n      n <- n - 1\nscrie n";
+        let terminated = "citeste n\npentru i <- 1, n executa\ndaca i % 2 = 0 atunci\n      scrie i\n altfel\ncat timp n > 0 executa\nn <- n - 1\nsfarsit cat timp\n  sf daca\nsfârșit pentru\nscrie n";
+        assert_eq!(detect_layout(indented), Layout::Indentation);
+        assert_eq!(detect_layout(terminated), Layout::Terminators);
+        assert_eq!(
+            program::<Error<&str>>(terminated).unwrap().1,
+            program::<Error<&str>>(indented).unwrap().1
+        );
+        assert_eq!(
+            program_with::<Error<&str>>(
+                "repeta\nx <- x + 1\npana cand x > 3\nexecuta\nx <- x - 1\ncat timp x > 0",
+                Layout::Terminators,
+                DEFAULT_TAB_WIDTH
+            )
+            .unwrap()
+            .1,
+            program::<Error<&str>>(
+                "repeta\n  x <- x + 1\npana cand x > 3\nexecuta\n  x <- x - 1\ncat timp x > 0"
+            )
+            .unwrap()
+            .1
+        );
+        assert!(program_with::<Error<&str>>(
+            "daca 1 atunci\nscrie 1",
+            Layout::Terminators,
+            DEFAULT_TAB_WIDTH
+        )
+        .is_err());
+        assert!(program_with::<Error<&str>>(
+            "daca 1 atunci\nscrie 1\nsfarsit pentru",
+            Layout::Terminators,
+            DEFAULT_TAB_WIDTH
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn indentation_test() {
+        let expected = program::<Error<&str>>(
+            "pentru i <- 1, 3 executa\n  daca i > 1 atunci\n    scrie i\n  altfel\n    scrie 0\nscrie 4",
+        )
+        .unwrap()
+        .1;
+        for source in [
+            "pentru i <- 1, 3 executa\n    daca i > 1 atunci\n        scrie i\n    altfel\n        scrie 0\nscrie 4",
+            "pentru i <- 1, 3 executa\r\n\tdaca i > 1 atunci\r\n\t\tscrie i\r\n\taltfel\r\n\t\tscrie 0\r\nscrie 4\r\n",
+            "pentru i <- 1, 3 executa\n\tdaca i > 1 atunci\n\t    scrie i\n    altfel\n        scrie 0\nscrie 4",
+            "pentru i <- 1, 3 executa\n daca i > 1 atunci\n     scrie i\n altfel\n   scrie 0\nscrie 4",
+        ] {
+            assert_eq!(program::<Error<&str>>(source).unwrap().1, expected);
+        }
+        assert_eq!(
+            program_with::<Error<&str>>(
+                "daca 1 atunci\n\tscrie 1\n  scrie 2",
+                Layout::Indentation,
+                2
+            )
+            .unwrap()
+            .1
+            .len(),
+            1
+        );
+        assert!(program::<Error<&str>>("daca 1 atunci\n    scrie 1\n  scrie 2").is_err());
+        assert!(program::<Error<&str>>("daca 1 atunci\nscrie 1").is_err());
+    }
 }
@@ -0,0 +1,86 @@
+use super::whitespace::space0;
+use nom::error::{ContextError, ErrorKind, ParseError};
+use nom::IResult;
+
+/// Maps a Romanian letter with diacritics to its ASCII spelling. Both the comma
+/// (`ș`, `ț`) and the older cedilla (`ş`, `ţ`) forms are accepted, since the
+/// official subjects use either of them.
+fn without_diacritic(c: char) -> char {
+    match c {
+        'ă' | 'â' => 'a',
+        'î' => 'i',
+        'ș' | 'ş' => 's',
+        'ț' | 'ţ' => 't',
+        c => c,
+    }
+}
+
+pub fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Matches a keyword given in its ASCII spelling, accepting diacritics in the
+/// input (`daca` matches both `daca` and `dacă`). The keyword must not be
+/// followed by another identifier character, so `scrie` does not match `scriere`.
+/// On failure the keyword itself is reported as what was expected.
+pub fn keyword<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    word: &'static str,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |i: &'a str| {
+        let error = || {
+            let error = E::from_error_kind(i, ErrorKind::Tag);
+            nom::Err::Error(E::add_context(i, word, error))
+        };
+        let mut chars = i.char_indices();
+        for expected in word.chars() {
+            match chars.next() {
+                Some((_, c)) if without_diacritic(c) == expected => {}
+                _ => return Err(error()),
+            }
+        }
+        let (matched, rest) = i.split_at(chars.offset());
+        if rest.starts_with(is_identifier_char) {
+            return Err(error());
+        }
+        Ok((rest, matched))
+    }
+}
+
+/// Matches keywords separated by spaces, such as `cat timp`, and the spaces
+/// after them.
+pub fn phrase<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    words: &'static str,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |i: &'a str| {
+        let mut rest = i;
+        for word in words.split(' ') {
+            rest = keyword(word)(rest)?.0;
+            rest = space0(rest)?.0;
+        }
+        Ok((rest, &i[..i.len() - rest.len()]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nom::error::Error;
+
+    #[test]
+    fn keyword_test() {
+        assert_eq!(keyword::<Error<&str>>("daca")("daca x"), Ok((" x", "daca")));
+        assert_eq!(keyword::<Error<&str>>("daca")("dacă x"), Ok((" x", "dacă")));
+        assert_eq!(
+            keyword::<Error<&str>>("citeste")("citește a"),
+            Ok((" a", "citește"))
+        );
+        assert_eq!(
+            keyword::<Error<&str>>("citeste")("citeşte a"),
+            Ok((" a", "citeşte"))
+        );
+        assert_eq!(keyword::<Error<&str>>("cat")("cât"), Ok(("", "cât")));
+        assert!(keyword::<Error<&str>>("scrie")("scriere").is_err());
+        assert!(keyword::<Error<&str>>("si")("sigma").is_err());
+        assert!(keyword::<Error<&str>>("pana")("pan").is_err());
+    }
+}
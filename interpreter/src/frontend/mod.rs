@@ -1,2 +1,5 @@
+pub mod error;
 pub mod expression;
 pub mod instruction;
+pub mod keyword;
+pub mod whitespace;
@@ -0,0 +1,96 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_until},
+    character::complete::{self, line_ending},
+    combinator::{opt, recognize},
+    error::{ErrorKind, ParseError},
+    multi::{many0_count, many1_count},
+    sequence::{delimited, preceded},
+    IResult,
+};
+
+/// A `// ...` comment running until the end of the line, or a `{ ... }` comment,
+/// which may span several lines.
+fn comment<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    alt((
+        recognize(preceded(tag("//"), opt(is_not("\n")))),
+        recognize(delimited(tag("{"), take_until("}"), tag("}"))),
+    ))(i)
+}
+
+/// Spaces, tabs and comments, without line breaks outside of comments.
+pub fn space0<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(many0_count(alt((complete::space1, comment))))(i)
+}
+
+pub fn space1<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(many1_count(alt((complete::space1, comment))))(i)
+}
+
+/// Any whitespace, line breaks included, and comments.
+pub fn multispace0<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(many0_count(alt((complete::multispace1, comment))))(i)
+}
+
+/// Moves to the beginning of the next line that contains code, skipping the
+/// rest of the current line and any line that is blank or only has comments.
+/// Returns the indentation of that line, spaces and tabs, with the input
+/// positioned after it. Lines may end in `\n` or `\r\n`.
+pub fn next_code_line<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    let (mut rest, _) = space0(i)?;
+    while let Ok((line, _)) = line_ending::<_, E>(rest) {
+        let (after_indentation, indentation) = complete::space0(line)?;
+        let (after_comments, _) = space0(after_indentation)?;
+        if !after_comments.is_empty() && line_ending::<_, E>(after_comments).is_err() {
+            return Ok((after_comments, indentation));
+        }
+        rest = after_comments;
+    }
+    Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::CrLf)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nom::error::Error;
+
+    #[test]
+    fn space_test() {
+        assert_eq!(space0::<Error<&str>>("  x"), Ok(("x", "  ")));
+        assert_eq!(
+            space0::<Error<&str>>(" // comentariu\nx"),
+            Ok(("\nx", " // comentariu"))
+        );
+        assert_eq!(
+            space1::<Error<&str>>(" { a\n b } x"),
+            Ok(("x", " { a\n b } "))
+        );
+        assert!(space1::<Error<&str>>("x").is_err());
+        assert_eq!(
+            multispace0::<Error<&str>>("\n // a\n{ b }\n  x"),
+            Ok(("x", "\n // a\n{ b }\n  "))
+        );
+    }
+
+    #[test]
+    fn next_code_line_test() {
+        assert_eq!(
+            next_code_line::<Error<&str>>("\n  scrie x"),
+            Ok(("scrie x", "  "))
+        );
+        assert_eq!(
+            next_code_line::<Error<&str>>(" // a\n\n    // b\n  { c } scrie x"),
+            Ok(("scrie x", "  "))
+        );
+        assert_eq!(
+            next_code_line::<Error<&str>>("\n{ a\n    b }\nscrie x"),
+            Ok(("scrie x", ""))
+        );
+        assert_eq!(
+            next_code_line::<Error<&str>>("\r\n\t\r\n \tscrie x\r\n"),
+            Ok(("scrie x\r\n", " \t"))
+        );
+        assert!(next_code_line::<Error<&str>>("\n  // a\n").is_err());
+        assert!(next_code_line::<Error<&str>>(" scrie x").is_err());
+    }
+}
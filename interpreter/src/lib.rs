@@ -1,43 +1,220 @@
 mod backend;
 mod frontend;
+mod transpiler;
 
-use backend::{execute_program, ExecutionError};
-use frontend::instruction;
-use std::{collections::HashMap, io::{Write, BufRead}};
+#[cfg(feature = "by-name")]
+use backend::execute_by_name;
+use backend::{execute_compiled, execute_program, resolve};
+pub use backend::{CancellationToken, ExecutionError, ExecutionErrorKind, Location};
+pub use frontend::error::Diagnostic;
+use frontend::error::SyntaxError;
+pub use frontend::instruction::Layout;
+use frontend::instruction::{self, Block};
+use std::{
+    fmt,
+    io::{BufRead, Write},
+    time::{Duration, Instant},
+};
+pub use transpiler::LoopKind;
 
 use backend::ExecutionContext;
-use nom::error::VerboseError;
+
+/// Settings for running a program. The defaults suit most programs.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// How blocks are closed. When missing, it is guessed from the program.
+    pub layout: Option<Layout>,
+    /// How many spaces a tab is worth when blocks are indented.
+    pub tab_width: usize,
+    /// How the program is run once it is parsed.
+    pub engine: Engine,
+    /// How many steps the program may execute before it is stopped. Each
+    /// instruction is a step, and so is each check of a loop condition.
+    pub max_steps: Option<u64>,
+    /// How long the program may run before it is stopped. The clock is not
+    /// available on every platform, such as WebAssembly in a browser.
+    pub time_limit: Option<Duration>,
+    /// Lets the program be stopped while it runs, by cancelling the token.
+    pub cancellation: Option<CancellationToken>,
+}
+
+/// How a parsed program is run. All engines give the same results.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Engine {
+    /// Compiles the program to bytecode for a virtual machine.
+    Bytecode,
+    /// Walks the syntax tree. It is slower, but serves as the reference.
+    TreeWalker,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            layout: None,
+            tab_width: instruction::DEFAULT_TAB_WIDTH,
+            engine: Engine::Bytecode,
+            max_steps: None,
+            time_limit: None,
+            cancellation: None,
+        }
+    }
+}
 
 pub fn interpret<'a>(
     input: &'a mut dyn BufRead,
-    output: &'a mut dyn Write, 
-    program_string: &'a str
-) -> Result<(), InterpreterError<'a>> {
-    let  (_, program) = instruction::program::<VerboseError<&str>>(program_string)?;
-    Box::leak(Box::new(32));
-    let mut execution_context = ExecutionContext {
-        integers: HashMap::new(),
-        input,
-        output
-    };
-    execute_program(&program, &mut execution_context)?;
+    output: &'a mut dyn Write,
+    program_string: &'a str,
+) -> Result<(), InterpreterError> {
+    interpret_with_options(input, output, program_string, &Options::default())
+}
+
+pub fn interpret_with_options<'a>(
+    input: &'a mut dyn BufRead,
+    output: &'a mut dyn Write,
+    program_string: &'a str,
+    options: &Options,
+) -> Result<(), InterpreterError> {
+    let program = resolve(parse(program_string, options)?);
+    let mut execution_context = ExecutionContext::new(program_string, input, output);
+    execution_context.max_steps = options.max_steps;
+    execution_context.deadline = options.time_limit.map(|limit| Instant::now() + limit);
+    execution_context.cancellation = options.cancellation.clone();
+    match options.engine {
+        Engine::Bytecode => execute_compiled(&program, &mut execution_context)?,
+        Engine::TreeWalker => execute_program(&program, &mut execution_context)?,
+    }
     Ok(())
 }
 
+/// Runs the program with the tree walker, looking each variable up by its name
+/// instead of by the slot it was resolved to. It is only there to measure what
+/// resolving the variables saves.
+#[cfg(feature = "by-name")]
+#[doc(hidden)]
+pub fn interpret_by_name<'a>(
+    input: &'a mut dyn BufRead,
+    output: &'a mut dyn Write,
+    program_string: &'a str,
+) -> Result<(), InterpreterError> {
+    let program = resolve(parse(program_string, &Options::default())?);
+    let mut execution_context = ExecutionContext::new(program_string, input, output);
+    execute_by_name(&program, &mut execution_context)?;
+    Ok(())
+}
+
+/// Writes the program in C++, without running it. Only the layout and tab
+/// width of the options are used.
+pub fn to_cpp(program_string: &str, options: &Options) -> Result<String, Diagnostic> {
+    Ok(transpiler::to_cpp(&parse(program_string, options)?))
+}
+
+/// Writes the program in Pascal, without running it. Only the layout and tab
+/// width of the options are used.
+pub fn to_pascal(program_string: &str, options: &Options) -> Result<String, Diagnostic> {
+    Ok(transpiler::to_pascal(&parse(program_string, options)?))
+}
+
+/// Writes the program in Python, without running it. Only the layout and tab
+/// width of the options are used.
+pub fn to_python(program_string: &str, options: &Options) -> Result<String, Diagnostic> {
+    Ok(transpiler::to_python(&parse(program_string, options)?))
+}
+
+/// Rewrites the loops of kind `from` into loops of kind `to` that do the same,
+/// and writes the program back as pseudocode, without its comments. Only the
+/// layout and tab width of the options are used. Loops cannot be rewritten
+/// as `pentru` loops.
+pub fn rewrite_loops(
+    program_string: &str,
+    from: LoopKind,
+    to: LoopKind,
+    options: &Options,
+) -> Result<String, RewriteError> {
+    let program = parse(program_string, options)?;
+    let step_names = transpiler::step_names(&program);
+    let program = transpiler::rewrite(program, from, to, &step_names)?;
+    Ok(transpiler::to_pseudocode(&program))
+}
+
+/// Writes the program back as canonical pseudocode: blocks indented by two
+/// spaces, one space around `<-` and operators, and only the parentheses the
+/// precedence of the operators asks for. Comments are lost. Only the layout
+/// and tab width of the options are used.
+pub fn format_program(program_string: &str, options: &Options) -> Result<String, Diagnostic> {
+    Ok(transpiler::to_pseudocode(&parse(program_string, options)?))
+}
+
+/// Whether two sources hold the same program, whatever their comments,
+/// spacing and redundant parentheses. It is only there for the tests of the
+/// formatter.
+#[doc(hidden)]
+pub fn same_program(
+    program_string: &str,
+    other_string: &str,
+    options: &Options,
+) -> Result<bool, Diagnostic> {
+    Ok(parse(program_string, options)? == parse(other_string, options)?)
+}
+
+fn parse<'a>(program_string: &'a str, options: &Options) -> Result<Block<'a>, Diagnostic> {
+    let layout = options
+        .layout
+        .unwrap_or_else(|| instruction::detect_layout(program_string));
+    let parsed =
+        instruction::program_with::<SyntaxError>(program_string, layout, options.tab_width);
+    let (_, program) = parsed.map_err(|e| e.into_diagnostic(program_string))?;
+    Ok(program)
+}
+
 #[derive(Debug)]
-pub enum InterpreterError<'a> {
-    ParsingError(VerboseError<&'a str>),
-    ExecutionError(ExecutionError)
+pub enum InterpreterError {
+    ParsingError(Diagnostic),
+    ExecutionError(ExecutionError),
+}
+
+impl fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpreterError::ParsingError(e) => write!(f, "Eroare de sintaxa. {}", e),
+            InterpreterError::ExecutionError(e) => write!(f, "Eroare la executie. {}", e),
+        }
+    }
 }
 
-impl<'a> From<VerboseError<&'a str>> for InterpreterError<'a> {
-    fn from(e: VerboseError<&'a str>) -> Self {
+impl From<Diagnostic> for InterpreterError {
+    fn from(e: Diagnostic) -> Self {
         InterpreterError::ParsingError(e)
     }
 }
 
-impl<'a> From<ExecutionError> for InterpreterError<'a> {
+impl From<ExecutionError> for InterpreterError {
     fn from(e: ExecutionError) -> Self {
         InterpreterError::ExecutionError(e)
     }
-}
\ No newline at end of file
+}
+
+/// Why the loops of a program could not be rewritten.
+#[derive(Debug)]
+pub enum RewriteError {
+    ParsingError(Diagnostic),
+    /// Loops were asked to become `pentru` loops, which count with a
+    /// variable of their own.
+    ForTarget,
+}
+
+impl fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewriteError::ParsingError(e) => write!(f, "Eroare de sintaxa. {}", e),
+            RewriteError::ForTarget => {
+                write!(f, "Structurile repetitive nu pot fi rescrise ca pentru")
+            }
+        }
+    }
+}
+
+impl From<Diagnostic> for RewriteError {
+    fn from(e: Diagnostic) -> Self {
+        RewriteError::ParsingError(e)
+    }
+}
@@ -1,10 +1,143 @@
-use interpreter::interpret;
-use std::{env, fs, path::Path};
+use interpreter::{
+    format_program, interpret_with_options, rewrite_loops, to_cpp, to_pascal, to_python, Layout,
+    LoopKind, Options, RewriteError,
+};
+use std::{env, fs, path::Path, process, str::FromStr, thread, time::Duration};
+
+/// Recursive subprograms need a deeper stack than the main thread gets.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+const USAGE: &str =
+    "Utilizare: interpreter [cpp | pascal | python | rescrie <din> <in> | fmt [--check]] \
+                     [--indentare | --sfarsit] [--tab <latime>] [--pasi <numar>] \
+                     [--timp <secunde>] <fisier>\n\
+     Structurile repetitive rescrise: pentru, cat-timp, executa, repeta (nu in pentru)";
+
+/// The languages a program can be written in, instead of running it.
+const LANGUAGES: [&str; 3] = ["cpp", "pascal", "python"];
 
 fn main() {
-    let file_name = env::args().nth(1).expect("Introduceti numele fisierului");
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Nu s-a putut porni interpretorul");
+    interpreter.join().unwrap();
+}
+
+/// What to do with the program.
+enum Command {
+    Run,
+    Translate(String),
+    RewriteLoops(LoopKind, LoopKind),
+    /// Formats the program, or only checks whether it is formatted.
+    Format {
+        check: bool,
+    },
+}
+
+fn run() {
+    let mut options = Options::default();
+    let mut file_name = None;
+    let mut arguments = env::args().skip(1).peekable();
+    let mut command = match arguments.next_if(|argument| {
+        LANGUAGES.contains(&argument.as_str()) || argument == "rescrie" || argument == "fmt"
+    }) {
+        None => Command::Run,
+        Some(argument) if argument == "rescrie" => {
+            let from = loop_kind(arguments.next());
+            let to = loop_kind(arguments.next());
+            Command::RewriteLoops(from, to)
+        }
+        Some(argument) if argument == "fmt" => Command::Format { check: false },
+        Some(language) => Command::Translate(language),
+    };
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--indentare" => options.layout = Some(Layout::Indentation),
+            "--sfarsit" => options.layout = Some(Layout::Terminators),
+            "--tab" => options.tab_width = value(arguments.next()),
+            "--pasi" => options.max_steps = Some(value(arguments.next())),
+            "--timp" => {
+                let seconds = value(arguments.next());
+                let limit = Duration::try_from_secs_f64(seconds).unwrap_or_else(|_| usage());
+                options.time_limit = Some(limit);
+            }
+            "--check" => match &mut command {
+                Command::Format { check } => *check = true,
+                _ => usage(),
+            },
+            _ if file_name.is_none() && !argument.starts_with("--") => file_name = Some(argument),
+            _ => usage(),
+        }
+    }
+    let file_name = file_name.expect(USAGE);
     let path = Path::new(&file_name);
     let input = fs::read_to_string(path).expect("Fisier invalid");
 
-    interpret(&mut std::io::stdin().lock(), &mut std::io::stdout(), &input).unwrap();
+    let generated = match command {
+        Command::Run => {
+            if let Err(e) = interpret_with_options(
+                &mut std::io::stdin().lock(),
+                &mut std::io::stdout(),
+                &input,
+                &options,
+            ) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+            return;
+        }
+        Command::Translate(language) => match language.as_str() {
+            "cpp" => to_cpp(&input, &options),
+            "pascal" => to_pascal(&input, &options),
+            _ => to_python(&input, &options),
+        },
+        Command::RewriteLoops(from, to) => match rewrite_loops(&input, from, to, &options) {
+            Ok(program) => Ok(program),
+            Err(RewriteError::ParsingError(e)) => Err(e),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        },
+        Command::Format { check: true } => match format_program(&input, &options) {
+            Ok(program) if program == input => return,
+            Ok(_) => {
+                eprintln!("Fisierul {} nu este formatat", file_name);
+                process::exit(1);
+            }
+            Err(e) => Err(e),
+        },
+        Command::Format { check: false } => format_program(&input, &options),
+    };
+    match generated {
+        Ok(program) => print!("{}", program),
+        Err(e) => {
+            eprintln!("Eroare de sintaxa. {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Parses the value that follows an option.
+fn value<T: FromStr>(argument: Option<String>) -> T {
+    argument
+        .and_then(|argument| argument.parse().ok())
+        .unwrap_or_else(|| usage())
+}
+
+/// Parses the name of a repetitive structure.
+fn loop_kind(argument: Option<String>) -> LoopKind {
+    match argument.as_deref() {
+        Some("pentru") => LoopKind::For,
+        Some("cat-timp") => LoopKind::While,
+        Some("executa") => LoopKind::DoWhile,
+        Some("repeta") => LoopKind::Repeat,
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
 }
@@ -0,0 +1,637 @@
+use std::collections::{BTreeSet, HashSet};
+
+use super::types::{definitions, subprograms, Base, Scope, Type, Types};
+use super::{
+    assigned, constant_step, identifier, Infix, Operators, Output, Step, ARRAY_LENGTH,
+    MATRIX_LENGTH,
+};
+use crate::backend::unescape;
+use crate::frontend::expression::{Expression, LValue};
+use crate::frontend::instruction::{Instruction, Statement, Subprogram, WhileType, Writable};
+
+const RESERVED: &[&str] = &[
+    "abs",
+    "and",
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "cat",
+    "char",
+    "cin",
+    "class",
+    "const",
+    "continue",
+    "cout",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "else",
+    "endl",
+    "enum",
+    "false",
+    "float",
+    "floor",
+    "for",
+    "goto",
+    "if",
+    "int",
+    "long",
+    "main",
+    "max",
+    "min",
+    "namespace",
+    "new",
+    "not",
+    "or",
+    "private",
+    "public",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "sqrt",
+    "static",
+    "std",
+    "string",
+    "struct",
+    "switch",
+    "template",
+    "text",
+    "this",
+    "true",
+    "try",
+    "typedef",
+    "union",
+    "unsigned",
+    "using",
+    "void",
+    "while",
+    "xor",
+];
+
+/// How tightly the C++ operators bind, from the loosest to the tightest.
+const OR: u8 = 1;
+const AND: u8 = 2;
+const EQUALITY: u8 = 3;
+const RELATIONAL: u8 = 4;
+const ADDITIVE: u8 = 5;
+const MULTIPLICATIVE: u8 = 6;
+const UNARY: u8 = 7;
+const PRIMARY: u8 = 8;
+
+/// Writes a real number like the pseudocode does, with at most six decimals,
+/// which `cout` does not do on its own.
+const TEXT: &str = "// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == \"-0\" ? \"0\" : text;
+}
+";
+
+/// Divides two numbers read from the input like the pseudocode does, which
+/// divides integers without a remainder. C++ only knows them as reals.
+const CAT: &str = "// Imparte fara rest cand ambele numere sunt intregi.
+double cat(double a, double b)
+{
+    if (a == trunc(a) && b == trunc(b)) {
+        return trunc(a / b);
+    }
+    return a / b;
+}
+";
+
+/// Writes the program in C++, the way it is asked for in the exam: reading
+/// with `cin`, writing with `cout`, and every variable declared at the start
+/// of the function it belongs to, with a type guessed from its values.
+/// Subprograms become functions defined before `main`. Arrays are passed by
+/// reference in C++, so a function that changes an array parameter works on
+/// a copy of it, as the pseudocode does.
+pub fn to_cpp(program: &[Statement]) -> String {
+    let types = Types::infer(program);
+    let subprograms = subprograms(program);
+    let mut cpp = Cpp {
+        types: &types,
+        scope: &types.main,
+        subprogram: None,
+        headers: BTreeSet::from(["iostream"]),
+        writes_reals: false,
+        divides_numbers: false,
+        out: Output::new("    "),
+    };
+
+    let definitions = definitions(program, &subprograms);
+    // Functions may call each other in any order once they are declared.
+    if definitions.len() > 1 {
+        for subprogram in &definitions {
+            let signature = cpp.signature(subprogram);
+            cpp.out.line(&format!("{};", signature));
+        }
+        cpp.out.line("");
+    }
+    for subprogram in &definitions {
+        cpp.function(subprogram);
+        cpp.out.line("");
+    }
+
+    cpp.scope = &types.main;
+    cpp.subprogram = None;
+    cpp.out.line("int main()");
+    cpp.out.line("{");
+    cpp.out.indent();
+    cpp.declarations(0);
+    cpp.block(program);
+    cpp.out.line("return 0;");
+    cpp.out.dedent();
+    cpp.out.line("}");
+
+    let mut header = String::new();
+    for name in &cpp.headers {
+        header += &format!("#include <{}>\n", name);
+    }
+    header += "using namespace std;\n\n";
+    if cpp.writes_reals {
+        header += TEXT;
+        header.push('\n');
+    }
+    if cpp.divides_numbers {
+        header += CAT;
+        header.push('\n');
+    }
+    header + &cpp.out.text
+}
+
+struct Cpp<'t, 'a> {
+    types: &'t Types<'a>,
+    scope: &'t Scope<'a>,
+    /// The function being written, or `None` for `main`.
+    subprogram: Option<&'t Subprogram<'a>>,
+    /// The standard headers the program needs.
+    headers: BTreeSet<&'static str>,
+    /// Whether the program writes real numbers, with the `text` function.
+    writes_reals: bool,
+    /// Whether the program divides numbers read from the input, with the
+    /// `cat` function.
+    divides_numbers: bool,
+    out: Output,
+}
+
+impl<'t, 'a> Cpp<'t, 'a> {
+    fn type_of(&self, expr: &Expression) -> Type {
+        self.types.of(self.scope, expr)
+    }
+
+    fn base_type(&mut self, base: Base) -> &'static str {
+        match base {
+            Base::Integer => "int",
+            Base::Number | Base::Real => "double",
+            Base::Character => "char",
+            Base::Text => {
+                self.headers.insert("string");
+                "string"
+            }
+        }
+    }
+
+    /// Declares `name` with its type, such as `v[1001]` for an array.
+    fn declarator(&self, name: &str, var_type: Type, parameter: bool) -> String {
+        let mut declarator = identifier(name, RESERVED);
+        let length = if var_type.dimensions > 1 {
+            MATRIX_LENGTH
+        } else {
+            ARRAY_LENGTH
+        };
+        for dimension in 0..var_type.dimensions {
+            if parameter && dimension == 0 {
+                declarator += "[]";
+            } else {
+                declarator += &format!("[{}]", length);
+            }
+        }
+        declarator
+    }
+
+    fn signature(&mut self, subprogram: &Subprogram) -> String {
+        let result = match self.types.results.get(subprogram.name) {
+            Some(result) => self.base_type(result.base),
+            None => "void",
+        };
+        let scope = &self.types.subprograms[subprogram.name];
+        let copied = copied(subprogram, scope);
+        let mut parameters = Vec::new();
+        for &(name, var_type) in &scope.variables[..subprogram.parameters.len()] {
+            let base = self.base_type(var_type.base);
+            let declarator = if copied.contains(name) {
+                self.declarator(&original(name), var_type, true)
+            } else {
+                self.declarator(name, var_type, true)
+            };
+            parameters.push(format!("{} {}", base, declarator));
+        }
+        format!(
+            "{} {}({})",
+            result,
+            identifier(subprogram.name, RESERVED),
+            parameters.join(", ")
+        )
+    }
+
+    fn function(&mut self, subprogram: &'t Subprogram<'a>) {
+        self.scope = &self.types.subprograms[subprogram.name];
+        self.subprogram = Some(subprogram);
+        let signature = self.signature(subprogram);
+        self.out.line(&signature);
+        self.out.line("{");
+        self.out.indent();
+        self.declarations(subprogram.parameters.len());
+        let copied = copied(subprogram, self.scope);
+        for &(name, var_type) in &self.scope.variables[..subprogram.parameters.len()] {
+            if copied.contains(name) {
+                let base = self.base_type(var_type.base);
+                let declarator = self.declarator(name, var_type, false);
+                self.out.line(&format!("{} {};", base, declarator));
+                self.headers.insert("cstring");
+                let copy = identifier(name, RESERVED);
+                self.out.line(&format!(
+                    "memcpy({}, {}, sizeof {});",
+                    copy,
+                    original(name),
+                    copy
+                ));
+            }
+        }
+        self.block(&subprogram.block);
+        self.out.dedent();
+        self.out.line("}");
+    }
+
+    /// Declares the variables of the scope, skipping its parameters. Those of
+    /// the same type are declared together.
+    fn declarations(&mut self, parameters: usize) {
+        let mut groups: Vec<(&'static str, Vec<String>)> = Vec::new();
+        for &(name, var_type) in &self.scope.variables[parameters..] {
+            let base = self.base_type(var_type.base);
+            let declarator = self.declarator(name, var_type, false);
+            match groups.iter_mut().find(|(group, _)| *group == base) {
+                Some((_, declarators)) => declarators.push(declarator),
+                None => groups.push((base, vec![declarator])),
+            }
+        }
+        for (base, declarators) in &groups {
+            self.out
+                .line(&format!("{} {};", base, declarators.join(", ")));
+        }
+    }
+
+    fn block(&mut self, block: &[Statement<'a>]) {
+        for statement in block {
+            self.instruction(&statement.instruction);
+        }
+    }
+
+    fn nested(&mut self, block: &[Statement<'a>]) {
+        self.out.indent();
+        self.block(block);
+        self.out.dedent();
+    }
+
+    fn instruction(&mut self, instruction: &Instruction<'a>) {
+        match instruction {
+            Instruction::Read(targets) => {
+                let targets: Vec<String> = targets.iter().map(|t| self.lvalue(t)).collect();
+                self.out.line(&format!("cin >> {};", targets.join(" >> ")));
+            }
+            Instruction::Write(writables, newline) => {
+                let mut items: Vec<String> = writables
+                    .iter()
+                    .map(|writable| match writable {
+                        Writable::Expression(expr) if self.type_of(expr).is_real() => {
+                            self.writes_reals = true;
+                            self.headers.extend(["iomanip", "sstream", "string"]);
+                            format!("text({})", self.expression(expr, OR))
+                        }
+                        Writable::Expression(expr) => self.expression(expr, ADDITIVE),
+                        Writable::String(text) => string_literal(&unescape(text)),
+                    })
+                    .collect();
+                if *newline {
+                    items.push("endl".to_string());
+                }
+                self.out.line(&format!("cout << {};", items.join(" << ")));
+            }
+            Instruction::Assignment(target, expr) => {
+                let target = self.lvalue(target);
+                let value = self.expression(expr, OR);
+                self.out.line(&format!("{} = {};", target, value));
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("if ({}) {{", cond));
+                self.if_rest(if_block, else_block.as_deref());
+            }
+            Instruction::While(WhileType::While, cond, block) => {
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("while ({}) {{", cond));
+                self.nested(block);
+                self.out.line("}");
+            }
+            Instruction::While(while_type, cond, block) => {
+                self.out.line("do {");
+                self.nested(block);
+                let cond = if *while_type == WhileType::Repeat {
+                    format!("!{}", self.expression(cond, PRIMARY))
+                } else {
+                    self.expression(cond, OR)
+                };
+                self.out.line(&format!("}} while ({});", cond));
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => {
+                let variable = identifier(variable, RESERVED);
+                let start = self.expression(start_expr, OR);
+                let end = self.expression(end_expr, RELATIONAL + 1);
+                let (cond, next) = match constant_step(step) {
+                    Some(Step::Ascending(size)) if size == "1" => (
+                        format!("{} <= {}", variable, end),
+                        format!("{}++", variable),
+                    ),
+                    Some(Step::Descending(size)) if size == "1" => (
+                        format!("{} >= {}", variable, end),
+                        format!("{}--", variable),
+                    ),
+                    Some(Step::Ascending(size)) => (
+                        format!("{} <= {}", variable, end),
+                        format!("{} += {}", variable, size),
+                    ),
+                    Some(Step::Descending(size)) => (
+                        format!("{} >= {}", variable, end),
+                        format!("{} -= {}", variable, size),
+                    ),
+                    // The direction is only known once the step is computed.
+                    None => {
+                        let step = self.expression(step, RELATIONAL + 1);
+                        (
+                            format!(
+                                "{} >= 0 ? {} <= {} : {} >= {}",
+                                step, variable, end, variable, end
+                            ),
+                            format!("{} += {}", variable, step),
+                        )
+                    }
+                };
+                self.out.line(&format!(
+                    "for ({} = {}; {}; {}) {{",
+                    variable, start, cond, next
+                ));
+                self.nested(block);
+                self.out.line("}");
+            }
+            Instruction::Call(name, arguments) => {
+                let call = self.call(name, arguments);
+                self.out.line(&format!("{};", call));
+            }
+            Instruction::Return(expr) => match (expr, self.subprogram) {
+                (Some(expr), Some(_)) => {
+                    let value = self.expression(expr, OR);
+                    self.out.line(&format!("return {};", value));
+                }
+                (_, Some(_)) => self.out.line("return;"),
+                (_, None) => self.out.line("return 0;"),
+            },
+            Instruction::Subprogram(_) => {}
+        }
+    }
+
+    /// Closes an `if`, chaining a lone `daca` of its `altfel` as `else if`.
+    fn if_rest(&mut self, if_block: &[Statement<'a>], else_block: Option<&[Statement<'a>]>) {
+        self.nested(if_block);
+        match else_block {
+            Some([statement]) => match &statement.instruction {
+                Instruction::If(cond, if_block, else_block) => {
+                    let cond = self.expression(cond, OR);
+                    self.out.line(&format!("}} else if ({}) {{", cond));
+                    self.if_rest(if_block, else_block.as_deref());
+                    return;
+                }
+                _ => {
+                    self.out.line("} else {");
+                    self.nested(std::slice::from_ref(statement));
+                }
+            },
+            Some(else_block) => {
+                self.out.line("} else {");
+                self.nested(else_block);
+            }
+            None => {}
+        }
+        self.out.line("}");
+    }
+
+    fn lvalue(&mut self, target: &LValue<'a>) -> String {
+        match target {
+            LValue::Variable(var) => identifier(var, RESERVED),
+            LValue::Index(var, indices) => self.index(var, indices),
+        }
+    }
+
+    fn index(&mut self, var: &str, indices: &[Expression<'a>]) -> String {
+        let mut index = identifier(var, RESERVED);
+        for expr in indices {
+            index += &format!("[{}]", self.expression(expr, OR));
+        }
+        index
+    }
+
+    /// Writes an operand of an operation on text. C++ only concatenates and
+    /// compares characters and literals when one side is a `string`.
+    fn text_operand(&mut self, expr: &Expression<'a>, other: &Expression<'a>) -> String {
+        let is_string = |cpp: &Self, expr: &Expression| {
+            cpp.type_of(expr).base == Base::Text && !matches!(expr, Expression::StringConstant(_))
+        };
+        let base = self.type_of(expr).base;
+        if base == Base::Text && (is_string(self, expr) || is_string(self, other)) {
+            return self.expression(expr, ADDITIVE);
+        }
+        self.headers.insert("string");
+        let operand = self.expression(expr, OR);
+        match base {
+            Base::Character => format!("string(1, {})", operand),
+            _ => format!("string({})", operand),
+        }
+    }
+
+    fn call(&mut self, name: &str, arguments: &[Expression<'a>]) -> String {
+        let is_subprogram = self.types.subprograms.contains_key(name);
+        match name {
+            "sqrt" | "radical" | "abs" | "modul" if !is_subprogram && arguments.len() == 1 => {
+                self.headers.insert("cmath");
+                let function = if name == "radical" || name == "sqrt" {
+                    "sqrt"
+                } else {
+                    "abs"
+                };
+                format!("{}({})", function, self.expression(&arguments[0], OR))
+            }
+            "lungime" if !is_subprogram && arguments.len() == 1 => {
+                format!("(int){}.length()", self.expression(&arguments[0], PRIMARY))
+            }
+            "max" | "min" if !is_subprogram && !arguments.is_empty() => {
+                self.headers.insert("algorithm");
+                let mixed = arguments
+                    .iter()
+                    .any(|argument| self.type_of(argument).base == Base::Integer)
+                    && arguments
+                        .iter()
+                        .any(|argument| self.type_of(argument).is_real());
+                let function = if mixed {
+                    format!("{}<double>", name)
+                } else {
+                    name.to_string()
+                };
+                let mut result = self.expression(&arguments[0], OR);
+                for argument in &arguments[1..] {
+                    let argument = self.expression(argument, OR);
+                    result = format!("{}({}, {})", function, result, argument);
+                }
+                result
+            }
+            _ => {
+                let arguments: Vec<String> = arguments
+                    .iter()
+                    .map(|argument| self.expression(argument, OR))
+                    .collect();
+                format!("{}({})", identifier(name, RESERVED), arguments.join(", "))
+            }
+        }
+    }
+}
+
+impl<'t, 'a> Infix<'a> for Cpp<'t, 'a> {
+    const OPERATORS: Operators = Operators {
+        or: ("||", OR),
+        and: ("&&", AND),
+        not: ("!", UNARY),
+        equal: "==",
+        not_equal: "!=",
+        equality: EQUALITY,
+        relational: RELATIONAL,
+        additive: ADDITIVE,
+        multiplicative: MULTIPLICATIVE,
+        minus: (UNARY, UNARY),
+        // Comparisons of comparisons get parentheses, for clarity.
+        grouped_comparisons: true,
+    };
+
+    fn operand(&mut self, expr: &Expression<'a>) -> (String, u8) {
+        match expr {
+            Expression::Constant(x) => (x.to_string(), PRIMARY),
+            Expression::RealConstant(x) => (format!("{:?}", x), PRIMARY),
+            Expression::StringConstant(text) => {
+                let text = unescape(text);
+                if text.chars().count() == 1 {
+                    (char_literal(text.chars().next().unwrap()), PRIMARY)
+                } else {
+                    (string_literal(&text), PRIMARY)
+                }
+            }
+            Expression::Variable(var) => (identifier(var, RESERVED), PRIMARY),
+            Expression::Index(var, indices) => (self.index(var, indices), PRIMARY),
+            Expression::IntegerPart(operand) => {
+                if self.type_of(operand).base == Base::Integer {
+                    return self.operation(operand);
+                }
+                self.headers.insert("cmath");
+                let operand = self.expression(operand, OR);
+                (format!("(int)floor({})", operand), UNARY)
+            }
+            Expression::Absolute(operand) => {
+                self.headers.insert("cmath");
+                (format!("abs({})", self.expression(operand, OR)), PRIMARY)
+            }
+            Expression::Call(name, arguments) => (self.call(name, arguments), PRIMARY),
+            _ => self.operation(expr),
+        }
+    }
+
+    /// Concatenations and comparisons of text need a `string` on one side,
+    /// and numbers read from the input are divided with `cat`.
+    fn special(&mut self, expr: &Expression<'a>) -> Option<(String, u8)> {
+        match expr {
+            Expression::Division(expr1, expr2) if self.type_of(expr).base == Base::Number => {
+                self.divides_numbers = true;
+                self.headers.insert("cmath");
+                let text1 = self.expression(expr1, OR);
+                let text2 = self.expression(expr2, OR);
+                Some((format!("cat({}, {})", text1, text2), PRIMARY))
+            }
+            Expression::Addition(expr1, expr2) if self.type_of(expr).is_textual() => {
+                let text1 = self.text_operand(expr1, expr2);
+                let text2 = self.expression(expr2, ADDITIVE + 1);
+                Some((format!("{} + {}", text1, text2), ADDITIVE))
+            }
+            Expression::Order(order_type, expr1, expr2)
+                if self.type_of(expr1).base == Base::Text
+                    || self.type_of(expr2).base == Base::Text =>
+            {
+                let (operator, precedence) = Self::OPERATORS.comparison(order_type);
+                let text1 = self.text_operand(expr1, expr2);
+                let text2 = if self.type_of(expr2).base == Base::Character {
+                    self.text_operand(expr2, expr1)
+                } else {
+                    self.expression(expr2, precedence + 1)
+                };
+                Some((format!("{} {} {}", text1, operator, text2), precedence))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The array parameters a subprogram changes, which it copies first.
+fn copied<'a>(subprogram: &Subprogram<'a>, scope: &Scope) -> HashSet<&'a str> {
+    let mut changed = HashSet::new();
+    assigned(&subprogram.block, &mut changed);
+    subprogram
+        .parameters
+        .iter()
+        .copied()
+        .filter(|&name| changed.contains(name) && scope.get(name).is_some_and(|t| t.dimensions > 0))
+        .collect()
+}
+
+/// The name of the array parameter a subprogram copies into `name`.
+fn original(name: &str) -> String {
+    format!("{}_initial", identifier(name, RESERVED))
+}
+
+fn escape(c: char, quote: char) -> String {
+    match c {
+        '\n' => "\\n".to_string(),
+        '\t' => "\\t".to_string(),
+        '\\' => "\\\\".to_string(),
+        c if c == quote => format!("\\{}", c),
+        c => c.to_string(),
+    }
+}
+
+fn char_literal(c: char) -> String {
+    format!("'{}'", escape(c, '\''))
+}
+
+fn string_literal(text: &str) -> String {
+    let escaped: String = text.chars().map(|c| escape(c, '"')).collect();
+    format!("\"{}\"", escaped)
+}
@@ -0,0 +1,379 @@
+use std::collections::HashSet;
+
+use super::{assigned, called, constant_step, variables, Step};
+use crate::frontend::expression::{Expression, LValue, OrderType};
+use crate::frontend::instruction::{Block, Instruction, Statement, WhileType, Writable};
+use crate::RewriteError;
+
+/// The repetitive structures of the pseudocode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoopKind {
+    /// `pentru v <- a, b, pas executa`
+    For,
+    /// `cat timp c executa`
+    While,
+    /// `executa ... cat timp c`
+    DoWhile,
+    /// `repeta ... pana cand c`
+    Repeat,
+}
+
+/// Rewrites every loop of kind `from` into an equivalent one of kind `to`,
+/// the way the exam asks for "un algoritm echivalent". A loop that checks its
+/// condition first becomes one that checks it last inside a `daca` with the
+/// same condition, and one that checks it last becomes one that checks it
+/// first after running its block once. A `pentru` loop counts with its own
+/// variable, so loops are never rewritten into one. The steps of rewritten
+/// `pentru` loops are kept in the variables named by `step_names`.
+pub fn rewrite<'a>(
+    program: Block<'a>,
+    from: LoopKind,
+    to: LoopKind,
+    step_names: &'a [String],
+) -> Result<Block<'a>, RewriteError> {
+    if to == LoopKind::For {
+        return Err(RewriteError::ForTarget);
+    }
+    let mut rewriter = Rewriter {
+        from,
+        to,
+        step_names,
+        depth: 0,
+    };
+    Ok(rewriter.block(program))
+}
+
+/// Names for the variables that keep the step of a rewritten `pentru` loop,
+/// one for each loop nested in another: `pas`, `pas2` and so on, skipping
+/// the names the program already uses.
+pub fn step_names(program: &[Statement]) -> Vec<String> {
+    let mut names = HashSet::new();
+    for statement in program {
+        if let Instruction::Subprogram(subprogram) = &statement.instruction {
+            names.insert(subprogram.name);
+            names.extend(&subprogram.parameters);
+            used_names(&subprogram.block, &mut names);
+        }
+    }
+    used_names(program, &mut names);
+    let mut step_names = Vec::new();
+    let mut number = 1;
+    while step_names.len() < kept_steps(program) {
+        let name = match number {
+            1 => "pas".to_string(),
+            number => format!("pas{}", number),
+        };
+        if !names.contains(name.as_str()) {
+            step_names.push(name);
+        }
+        number += 1;
+    }
+    step_names
+}
+
+struct Rewriter<'a> {
+    from: LoopKind,
+    to: LoopKind,
+    /// The names that do not clash with those of the program.
+    step_names: &'a [String],
+    /// How many step variables the loops around the current block hold.
+    depth: usize,
+}
+
+impl<'a> Rewriter<'a> {
+    fn block(&mut self, block: Block<'a>) -> Block<'a> {
+        let mut rewritten = Vec::with_capacity(block.len());
+        for statement in block {
+            self.statement(statement, &mut rewritten);
+        }
+        rewritten
+    }
+
+    /// Rewrites a statement into the statements of `block`.
+    fn statement(&mut self, statement: Statement<'a>, block: &mut Block<'a>) {
+        let instruction = match statement.instruction {
+            Instruction::If(cond, if_block, else_block) => Instruction::If(
+                cond,
+                self.block(if_block),
+                else_block.map(|else_block| self.block(else_block)),
+            ),
+            Instruction::While(while_type, cond, body) => {
+                let body = self.block(body);
+                let kind = match while_type {
+                    WhileType::While => LoopKind::While,
+                    WhileType::DoWhile => LoopKind::DoWhile,
+                    WhileType::Repeat => LoopKind::Repeat,
+                };
+                if kind == self.from {
+                    block.extend(self.convert(kind, cond, body));
+                    return;
+                }
+                Instruction::While(while_type, cond, body)
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block: body,
+            } if self.from == LoopKind::For => {
+                let step_name = keeps_step(variable, &step, &body).then(|| self.step_name());
+                self.depth += usize::from(step_name.is_some());
+                let body = self.block(body);
+                self.depth -= usize::from(step_name.is_some());
+                block.extend(self.for_loop(variable, start_expr, end_expr, step, step_name, body));
+                return;
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block: body,
+            } => Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block: self.block(body),
+            },
+            Instruction::Subprogram(mut subprogram) => {
+                subprogram.block = self.block(subprogram.block);
+                Instruction::Subprogram(subprogram)
+            }
+            instruction => instruction,
+        };
+        block.push(Statement {
+            instruction,
+            span: statement.span,
+        });
+    }
+
+    /// Writes a loop of `kind`, already rewritten inside, as a loop of the
+    /// target kind.
+    fn convert(&self, kind: LoopKind, cond: Expression<'a>, body: Block<'a>) -> Block<'a> {
+        // The condition to stop on, for `repeta`, or to go on, otherwise.
+        let (go_on, stop) = match kind {
+            LoopKind::Repeat => (negation(cond.clone()), cond),
+            _ => (cond.clone(), negation(cond)),
+        };
+        let target = match self.to {
+            LoopKind::While => Instruction::While(WhileType::While, go_on.clone(), body.clone()),
+            LoopKind::DoWhile => {
+                Instruction::While(WhileType::DoWhile, go_on.clone(), body.clone())
+            }
+            LoopKind::Repeat => Instruction::While(WhileType::Repeat, stop, body.clone()),
+            LoopKind::For => unreachable!(),
+        };
+        match (kind, self.to) {
+            (LoopKind::While, LoopKind::While) => vec![target.into()],
+            // The block runs once before the condition is first checked.
+            (_, LoopKind::While) => {
+                let mut block = body;
+                block.push(target.into());
+                block
+            }
+            // The condition is checked before the block first runs.
+            (LoopKind::While, _) => vec![Instruction::If(go_on, vec![target.into()], None).into()],
+            (_, _) => vec![target.into()],
+        }
+    }
+
+    /// Writes `pentru variable <- start, end, step` as a `cat timp` loop that
+    /// adds the step to the variable at the end of its block, then as a loop
+    /// of the target kind.
+    fn for_loop(
+        &self,
+        variable: &'a str,
+        start_expr: Expression<'a>,
+        end_expr: Expression<'a>,
+        step: Expression<'a>,
+        step_name: Option<&'a str>,
+        mut body: Block<'a>,
+    ) -> Block<'a> {
+        let var = || Box::new(Expression::Variable(variable));
+        let mut block =
+            vec![Instruction::Assignment(LValue::Variable(variable), start_expr).into()];
+        let compare = |order_type, end_expr: &Expression<'a>| {
+            Expression::Order(order_type, var(), Box::new(end_expr.clone()))
+        };
+        let (cond, next) = match constant_step(&step) {
+            Some(Step::Ascending(_)) => (
+                compare(OrderType::LessOrEqual, &end_expr),
+                Expression::Addition(var(), Box::new(step)),
+            ),
+            Some(Step::Descending(_)) => {
+                let size = match step {
+                    Expression::Minus(size) => size,
+                    step => Box::new(Expression::Minus(Box::new(step))),
+                };
+                (
+                    compare(OrderType::GreaterOrEqual, &end_expr),
+                    Expression::Subtraction(var(), size),
+                )
+            }
+            None => {
+                let step = match step_name {
+                    Some(name) => {
+                        block.push(Instruction::Assignment(LValue::Variable(name), step).into());
+                        Expression::Variable(name)
+                    }
+                    None => step,
+                };
+                let sign = |order_type| {
+                    Box::new(Expression::Order(
+                        order_type,
+                        Box::new(step.clone()),
+                        Box::new(Expression::Constant(0)),
+                    ))
+                };
+                let cond = Expression::Or(
+                    Box::new(Expression::And(
+                        sign(OrderType::GreaterOrEqual),
+                        Box::new(compare(OrderType::LessOrEqual, &end_expr)),
+                    )),
+                    Box::new(Expression::And(
+                        sign(OrderType::Less),
+                        Box::new(compare(OrderType::GreaterOrEqual, &end_expr)),
+                    )),
+                );
+                (cond, Expression::Addition(var(), Box::new(step)))
+            }
+        };
+        body.push(Instruction::Assignment(LValue::Variable(variable), next).into());
+        block.extend(self.convert(LoopKind::While, cond, body));
+        block
+    }
+
+    fn step_name(&self) -> &'a str {
+        &self.step_names[self.depth]
+    }
+}
+
+/// How many `pentru` loops that keep their step are nested at most.
+fn kept_steps(block: &[Statement]) -> usize {
+    let mut depth = 0;
+    for statement in block {
+        let nested = match &statement.instruction {
+            Instruction::If(_, if_block, else_block) => {
+                kept_steps(if_block).max(else_block.as_deref().map_or(0, kept_steps))
+            }
+            Instruction::While(_, _, block) => kept_steps(block),
+            Instruction::For {
+                variable,
+                step,
+                block,
+                ..
+            } => usize::from(keeps_step(variable, step, block)) + kept_steps(block),
+            Instruction::Subprogram(subprogram) => kept_steps(&subprogram.block),
+            _ => 0,
+        };
+        depth = depth.max(nested);
+    }
+    depth
+}
+
+/// Whether the step of a `pentru` loop needs a variable of its own. The
+/// step is computed once, when the loop starts, so unless it is a constant
+/// or a variable the block leaves alone, it is kept. A program whose
+/// `pentru` loop fails because its step changes keeps going once
+/// rewritten.
+fn keeps_step(variable: &str, step: &Expression, body: &[Statement]) -> bool {
+    if constant_step(step).is_some() {
+        return false;
+    }
+    let mut changed = HashSet::from([variable]);
+    assigned(body, &mut changed);
+    !matches!(step, Expression::Variable(name) if !changed.contains(name))
+}
+
+/// The opposite of the condition `cond`, turning comparisons around. The
+/// operands of `si` and `sau` are still evaluated in the same order, and only
+/// when needed, once they are negated.
+fn negation(cond: Expression) -> Expression {
+    match cond {
+        Expression::And(cond1, cond2) => {
+            Expression::Or(Box::new(negation(*cond1)), Box::new(negation(*cond2)))
+        }
+        Expression::Or(cond1, cond2) => {
+            Expression::And(Box::new(negation(*cond1)), Box::new(negation(*cond2)))
+        }
+        Expression::Order(order_type, expr1, expr2) => {
+            let opposite = match order_type {
+                OrderType::Less => OrderType::GreaterOrEqual,
+                OrderType::LessOrEqual => OrderType::Greater,
+                OrderType::Equal => OrderType::NotEqual,
+                OrderType::NotEqual => OrderType::Equal,
+                OrderType::Greater => OrderType::LessOrEqual,
+                OrderType::GreaterOrEqual => OrderType::Less,
+            };
+            Expression::Order(opposite, expr1, expr2)
+        }
+        Expression::Not(cond) => *cond,
+        cond => Expression::Not(Box::new(cond)),
+    }
+}
+
+/// The names of the variables and subprograms a block uses.
+fn used_names<'a>(block: &[Statement<'a>], names: &mut HashSet<&'a str>) {
+    assigned(block, names);
+    called(block, names);
+    let mut read = Vec::new();
+    for statement in block {
+        match &statement.instruction {
+            Instruction::Read(targets) => {
+                for target in targets {
+                    if let LValue::Index(_, indices) = target {
+                        indices.iter().for_each(|index| variables(index, &mut read));
+                    }
+                }
+            }
+            Instruction::Write(writables, _) => {
+                for writable in writables {
+                    if let Writable::Expression(expr) = writable {
+                        variables(expr, &mut read);
+                    }
+                }
+            }
+            Instruction::Assignment(target, expr) => {
+                if let LValue::Index(_, indices) = target {
+                    indices.iter().for_each(|index| variables(index, &mut read));
+                }
+                variables(expr, &mut read);
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                variables(cond, &mut read);
+                used_names(if_block, names);
+                if let Some(else_block) = else_block {
+                    used_names(else_block, names);
+                }
+            }
+            Instruction::While(_, cond, block) => {
+                variables(cond, &mut read);
+                used_names(block, names);
+            }
+            Instruction::For {
+                start_expr,
+                end_expr,
+                step,
+                block,
+                ..
+            } => {
+                for expr in [start_expr, end_expr, step] {
+                    variables(expr, &mut read);
+                }
+                used_names(block, names);
+            }
+            Instruction::Call(_, arguments) => {
+                arguments
+                    .iter()
+                    .for_each(|argument| variables(argument, &mut read));
+            }
+            Instruction::Return(expr) => {
+                expr.iter().for_each(|expr| variables(expr, &mut read));
+            }
+            Instruction::Subprogram(_) => {}
+        }
+    }
+    names.extend(read);
+}
@@ -0,0 +1,482 @@
+use std::collections::HashSet;
+
+use crate::frontend::expression::{Expression, LValue, OrderType};
+use crate::frontend::instruction::{Instruction, Statement, Writable};
+
+mod cpp;
+mod loops;
+mod pascal;
+mod pseudocode;
+mod python;
+mod types;
+
+pub use cpp::to_cpp;
+pub use loops::{rewrite, step_names, LoopKind};
+pub use pascal::to_pascal;
+pub use pseudocode::to_pseudocode;
+pub use python::to_python;
+
+/// Arrays get a fixed length, large enough for the usual exercises.
+const ARRAY_LENGTH: usize = 1001;
+const MATRIX_LENGTH: usize = 101;
+
+/// Generated source code, where each open block indents its lines one more
+/// level.
+struct Output {
+    text: String,
+    depth: usize,
+    indentation: &'static str,
+}
+
+impl Output {
+    fn new(indentation: &'static str) -> Self {
+        Output {
+            text: String::new(),
+            depth: 0,
+            indentation,
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            self.text += &self.indentation.repeat(self.depth);
+        }
+        self.text += line;
+        self.text.push('\n');
+    }
+
+    fn indent(&mut self) {
+        self.depth += 1;
+    }
+
+    fn dedent(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+/// The operators of a language, with how tightly each binds, from the
+/// loosest to the tightest.
+struct Operators {
+    or: (&'static str, u8),
+    and: (&'static str, u8),
+    /// Written right before its operand, which binds as tightly as it does.
+    not: (&'static str, u8),
+    equal: &'static str,
+    not_equal: &'static str,
+    equality: u8,
+    relational: u8,
+    additive: u8,
+    multiplicative: u8,
+    /// How tightly a minus sign binds, and how tightly its operand must.
+    minus: (u8, u8),
+    /// Whether comparisons of comparisons get parentheses on both sides.
+    grouped_comparisons: bool,
+}
+
+impl Operators {
+    /// The operator of a comparison, and how tightly it binds.
+    fn comparison(&self, order_type: &OrderType) -> (&'static str, u8) {
+        match order_type {
+            OrderType::Equal => (self.equal, self.equality),
+            OrderType::NotEqual => (self.not_equal, self.equality),
+            OrderType::Less => ("<", self.relational),
+            OrderType::LessOrEqual => ("<=", self.relational),
+            OrderType::Greater => (">", self.relational),
+            OrderType::GreaterOrEqual => (">=", self.relational),
+        }
+    }
+}
+
+/// Writes expressions with the operators of a language, adding only the
+/// parentheses their precedence asks for. The language writes the operands
+/// and the operations it does its own way, and its table of operators gives
+/// the rest.
+trait Infix<'a> {
+    const OPERATORS: Operators;
+
+    /// Writes an expression that is not an operation, with how tightly it
+    /// binds.
+    fn operand(&mut self, expr: &Expression<'a>) -> (String, u8);
+
+    /// Writes `expr` if it is an operation the language writes its own way.
+    fn special(&mut self, _expr: &Expression<'a>) -> Option<(String, u8)> {
+        None
+    }
+
+    /// Writes `expr`, in parentheses unless its operator binds at least as
+    /// tightly as `precedence`.
+    fn expression(&mut self, expr: &Expression<'a>, precedence: u8) -> String {
+        let (text, binding) = self.operation(expr);
+        parenthesized(text, binding, precedence)
+    }
+
+    fn operation(&mut self, expr: &Expression<'a>) -> (String, u8) {
+        if let Some(written) = self.special(expr) {
+            return written;
+        }
+        let operators = &Self::OPERATORS;
+        match expr {
+            Expression::Addition(expr1, expr2) => {
+                self.binary(expr1, "+", expr2, operators.additive)
+            }
+            Expression::Subtraction(expr1, expr2) => {
+                self.binary(expr1, "-", expr2, operators.additive)
+            }
+            Expression::Multiplication(expr1, expr2) => {
+                self.binary(expr1, "*", expr2, operators.multiplicative)
+            }
+            Expression::Division(expr1, expr2) => {
+                self.binary(expr1, "/", expr2, operators.multiplicative)
+            }
+            Expression::Reminder(expr1, expr2) => {
+                self.binary(expr1, "%", expr2, operators.multiplicative)
+            }
+            Expression::Order(order_type, expr1, expr2) => {
+                let (operator, precedence) = operators.comparison(order_type);
+                if !operators.grouped_comparisons {
+                    return self.binary(expr1, operator, expr2, precedence);
+                }
+                let text1 = self.expression(expr1, operators.relational + 1);
+                let text2 = self.expression(expr2, operators.relational + 1);
+                (format!("{} {} {}", text1, operator, text2), precedence)
+            }
+            Expression::And(expr1, expr2) => {
+                let (operator, precedence) = operators.and;
+                self.binary(expr1, operator, expr2, precedence)
+            }
+            Expression::Or(expr1, expr2) => {
+                let (operator, precedence) = operators.or;
+                self.binary(expr1, operator, expr2, precedence)
+            }
+            Expression::Not(operand) => {
+                let (operator, precedence) = operators.not;
+                let operand = self.expression(operand, precedence);
+                (format!("{}{}", operator, operand), precedence)
+            }
+            Expression::Minus(operand) => {
+                let (binding, precedence) = operators.minus;
+                let operand = self.expression(operand, precedence);
+                // Two signs in a row could be read as a decrement.
+                if operand.starts_with('-') {
+                    (format!("-({})", operand), binding)
+                } else {
+                    (format!("-{}", operand), binding)
+                }
+            }
+            _ => self.operand(expr),
+        }
+    }
+
+    fn binary(
+        &mut self,
+        expr1: &Expression<'a>,
+        operator: &str,
+        expr2: &Expression<'a>,
+        precedence: u8,
+    ) -> (String, u8) {
+        let text1 = self.expression(expr1, precedence);
+        let text2 = self.expression(expr2, precedence + 1);
+        (format!("{} {} {}", text1, operator, text2), precedence)
+    }
+}
+
+fn parenthesized(text: String, binding: u8, precedence: u8) -> String {
+    if binding < precedence {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+/// Turns a pseudocode name into one the target language accepts, spelling
+/// Romanian letters without diacritics and avoiding the words it reserves.
+fn identifier(name: &str, reserved: &[&str]) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| match c {
+            'ă' | 'â' => 'a',
+            'Ă' | 'Â' => 'A',
+            'î' => 'i',
+            'Î' => 'I',
+            'ș' | 'ş' => 's',
+            'Ș' | 'Ş' => 'S',
+            'ț' | 'ţ' => 't',
+            'Ț' | 'Ţ' => 'T',
+            c => c,
+        })
+        .collect();
+    if reserved.contains(&identifier.to_lowercase().as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+/// The sign of a constant `pentru` step, when it can be known without running
+/// the program.
+fn constant_step(step: &Expression) -> Option<Step> {
+    match step {
+        &Expression::Constant(x) => Some(Step::Ascending(x.to_string())),
+        &Expression::RealConstant(x) => Some(Step::Ascending(format!("{:?}", x))),
+        Expression::Minus(step) => match constant_step(step)? {
+            Step::Ascending(size) => Some(Step::Descending(size)),
+            Step::Descending(size) => Some(Step::Ascending(size)),
+        },
+        _ => None,
+    }
+}
+
+/// A constant step, written without its sign.
+enum Step {
+    Ascending(String),
+    Descending(String),
+}
+
+/// `expr + delta`, folded into `expr` when it is a constant or ends by taking
+/// away `delta`. Bounds and indices often need one added or taken away.
+fn shifted<'a>(expr: &Expression<'a>, delta: i32) -> Expression<'a> {
+    match expr {
+        Expression::Constant(x) => match x.checked_add(delta) {
+            Some(sum) if sum >= 0 => Expression::Constant(sum),
+            Some(sum) => Expression::Minus(Box::new(Expression::Constant(-sum))),
+            None => Expression::Addition(
+                Box::new(expr.clone()),
+                Box::new(Expression::Constant(delta)),
+            ),
+        },
+        Expression::Subtraction(expr, amount) if **amount == Expression::Constant(delta) => {
+            (**expr).clone()
+        }
+        Expression::Addition(expr, amount) if **amount == Expression::Constant(-delta) => {
+            (**expr).clone()
+        }
+        _ if delta < 0 => Expression::Subtraction(
+            Box::new(expr.clone()),
+            Box::new(Expression::Constant(-delta)),
+        ),
+        _ => Expression::Addition(
+            Box::new(expr.clone()),
+            Box::new(Expression::Constant(delta)),
+        ),
+    }
+}
+
+/// The subprograms a block calls.
+fn called<'a>(block: &[Statement<'a>], calls: &mut HashSet<&'a str>) {
+    let mut expressions = Vec::new();
+    for statement in block {
+        match &statement.instruction {
+            Instruction::Read(targets) => {
+                for target in targets {
+                    if let LValue::Index(_, indices) = target {
+                        expressions.extend(indices);
+                    }
+                }
+            }
+            Instruction::Write(writables, _) => {
+                for writable in writables {
+                    if let Writable::Expression(expr) = writable {
+                        expressions.push(expr);
+                    }
+                }
+            }
+            Instruction::Assignment(target, expr) => {
+                if let LValue::Index(_, indices) = target {
+                    expressions.extend(indices);
+                }
+                expressions.push(expr);
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                expressions.push(cond);
+                called(if_block, calls);
+                if let Some(else_block) = else_block {
+                    called(else_block, calls);
+                }
+            }
+            Instruction::While(_, cond, block) => {
+                expressions.push(cond);
+                called(block, calls);
+            }
+            Instruction::For {
+                start_expr,
+                end_expr,
+                step,
+                block,
+                ..
+            } => {
+                expressions.extend([start_expr, end_expr, step]);
+                called(block, calls);
+            }
+            Instruction::Call(name, arguments) => {
+                calls.insert(name);
+                expressions.extend(arguments);
+            }
+            Instruction::Return(expr) => expressions.extend(expr),
+            Instruction::Subprogram(_) => {}
+        }
+    }
+    while let Some(expr) = expressions.pop() {
+        match expr {
+            Expression::Constant(_)
+            | Expression::RealConstant(_)
+            | Expression::StringConstant(_)
+            | Expression::Variable(_) => {}
+            Expression::Index(_, indices) => expressions.extend(indices),
+            Expression::Addition(expr1, expr2)
+            | Expression::Subtraction(expr1, expr2)
+            | Expression::Multiplication(expr1, expr2)
+            | Expression::Division(expr1, expr2)
+            | Expression::Reminder(expr1, expr2)
+            | Expression::Order(_, expr1, expr2)
+            | Expression::And(expr1, expr2)
+            | Expression::Or(expr1, expr2) => expressions.extend([&**expr1, &**expr2]),
+            Expression::Minus(expr)
+            | Expression::Not(expr)
+            | Expression::IntegerPart(expr)
+            | Expression::Absolute(expr) => expressions.push(expr),
+            Expression::Call(name, arguments) => {
+                calls.insert(name);
+                expressions.extend(arguments);
+            }
+        }
+    }
+}
+
+/// Whether a `pentru` loop runs a number of times known when it starts, so it
+/// can be written with a counted loop of the target language: its body
+/// changes neither the loop variable nor the variables of the end value.
+fn is_counted(variable: &str, end_expr: &Expression, block: &[Statement]) -> bool {
+    let mut changed = HashSet::new();
+    assigned(block, &mut changed);
+    let mut read = vec![variable];
+    variables(end_expr, &mut read);
+    !read.iter().any(|variable| changed.contains(variable))
+}
+
+/// The variables a block assigns to, including arrays whose elements change.
+fn assigned<'a>(block: &[Statement<'a>], changed: &mut HashSet<&'a str>) {
+    let target = |target: &LValue<'a>| match target {
+        LValue::Variable(var) | LValue::Index(var, _) => *var,
+    };
+    for statement in block {
+        match &statement.instruction {
+            Instruction::Read(targets) => changed.extend(targets.iter().map(target)),
+            Instruction::Assignment(lvalue, _) => {
+                changed.insert(target(lvalue));
+            }
+            Instruction::If(_, if_block, else_block) => {
+                assigned(if_block, changed);
+                if let Some(else_block) = else_block {
+                    assigned(else_block, changed);
+                }
+            }
+            Instruction::While(_, _, block) => assigned(block, changed),
+            Instruction::For {
+                variable, block, ..
+            } => {
+                changed.insert(variable);
+                assigned(block, changed);
+            }
+            Instruction::Write(_, _)
+            | Instruction::Call(_, _)
+            | Instruction::Return(_)
+            | Instruction::Subprogram(_) => {}
+        }
+    }
+}
+
+/// The variables a block reads outside the `pentru` loops that count with
+/// them. Such a loop leaves its variable one step past the end, or untouched
+/// when it does not run, which the counted loops of other languages do not.
+fn read_outside_loops<'a>(block: &[Statement<'a>], read: &mut HashSet<&'a str>) {
+    let mut expressions = Vec::new();
+    for statement in block {
+        match &statement.instruction {
+            Instruction::Read(targets) => {
+                for target in targets {
+                    if let LValue::Index(_, indices) = target {
+                        expressions.extend(indices);
+                    }
+                }
+            }
+            Instruction::Write(writables, _) => {
+                for writable in writables {
+                    if let Writable::Expression(expr) = writable {
+                        expressions.push(expr);
+                    }
+                }
+            }
+            Instruction::Assignment(target, expr) => {
+                if let LValue::Index(_, indices) = target {
+                    expressions.extend(indices);
+                }
+                expressions.push(expr);
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                expressions.push(cond);
+                read_outside_loops(if_block, read);
+                if let Some(else_block) = else_block {
+                    read_outside_loops(else_block, read);
+                }
+            }
+            Instruction::While(_, cond, block) => {
+                expressions.push(cond);
+                read_outside_loops(block, read);
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => {
+                expressions.extend([start_expr, end_expr, step]);
+                let mut inside = HashSet::new();
+                read_outside_loops(block, &mut inside);
+                inside.remove(variable);
+                read.extend(inside);
+            }
+            Instruction::Call(_, arguments) => expressions.extend(arguments),
+            Instruction::Return(expr) => expressions.extend(expr),
+            Instruction::Subprogram(_) => {}
+        }
+    }
+    let mut variables_read = Vec::new();
+    for expr in expressions {
+        variables(expr, &mut variables_read);
+    }
+    read.extend(variables_read);
+}
+
+/// The variables `expr` reads.
+fn variables<'a>(expr: &Expression<'a>, read: &mut Vec<&'a str>) {
+    match expr {
+        Expression::Constant(_) | Expression::RealConstant(_) | Expression::StringConstant(_) => {}
+        Expression::Variable(var) => read.push(var),
+        Expression::Index(var, indices) => {
+            read.push(var);
+            for index in indices {
+                variables(index, read);
+            }
+        }
+        Expression::Addition(expr1, expr2)
+        | Expression::Subtraction(expr1, expr2)
+        | Expression::Multiplication(expr1, expr2)
+        | Expression::Division(expr1, expr2)
+        | Expression::Reminder(expr1, expr2)
+        | Expression::Order(_, expr1, expr2)
+        | Expression::And(expr1, expr2)
+        | Expression::Or(expr1, expr2) => {
+            variables(expr1, read);
+            variables(expr2, read);
+        }
+        Expression::Minus(expr)
+        | Expression::Not(expr)
+        | Expression::IntegerPart(expr)
+        | Expression::Absolute(expr) => variables(expr, read),
+        Expression::Call(_, arguments) => {
+            for argument in arguments {
+                variables(argument, read);
+            }
+        }
+    }
+}
@@ -0,0 +1,793 @@
+use std::collections::{BTreeSet, HashSet};
+
+use super::types::{definitions, subprograms, Base, Scope, Type, Types};
+use super::{
+    called, constant_step, identifier, is_counted, parenthesized, read_outside_loops, shifted,
+    Infix, Operators, Output, Step, ARRAY_LENGTH, MATRIX_LENGTH,
+};
+use crate::backend::unescape;
+use crate::frontend::expression::{Expression, LValue, OrderType};
+use crate::frontend::instruction::{Instruction, Statement, Subprogram, WhileType, Writable};
+
+const RESERVED: &[&str] = &[
+    "abs",
+    "and",
+    "array",
+    "begin",
+    "boolean",
+    "case",
+    "cat",
+    "char",
+    "const",
+    "div",
+    "do",
+    "downto",
+    "else",
+    "end",
+    "exit",
+    "false",
+    "file",
+    "floor",
+    "for",
+    "function",
+    "goto",
+    "if",
+    "in",
+    "integer",
+    "label",
+    "length",
+    "longint",
+    "math",
+    "matrice",
+    "max",
+    "min",
+    "mod",
+    "nil",
+    "not",
+    "of",
+    "or",
+    "ord",
+    "packed",
+    "procedure",
+    "program",
+    "read",
+    "real",
+    "record",
+    "repeat",
+    "set",
+    "sqrt",
+    "string",
+    "text",
+    "then",
+    "to",
+    "true",
+    "type",
+    "until",
+    "uses",
+    "var",
+    "vector",
+    "while",
+    "with",
+    "write",
+    "writeln",
+    "xor",
+];
+
+/// How tightly the Pascal operators bind, from the loosest to the tightest.
+/// Unlike in the pseudocode, `and` and `or` bind tighter than comparisons.
+const RELATIONAL: u8 = 1;
+const ADDITIVE: u8 = 2;
+const MULTIPLICATIVE: u8 = 3;
+const UNARY: u8 = 4;
+const PRIMARY: u8 = 5;
+
+/// Subprograms the generated program defines for the pseudocode operations
+/// Pascal does differently.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Helper {
+    Read,
+    Text,
+    Quotient,
+}
+
+impl Helper {
+    fn source(self) -> &'static str {
+        match self {
+            Helper::Read => {
+                "{ Citeste urmatorul cuvant, sarind peste spatii si randuri noi. }
+procedure citeste(var cuvant: string);
+var
+  c: char;
+begin
+  cuvant := '';
+  repeat
+    read(c);
+  until eof or (c > ' ');
+  while c > ' ' do begin
+    cuvant := cuvant + c;
+    if eof then begin
+      exit;
+    end;
+    read(c);
+  end;
+end;
+"
+            }
+            Helper::Text => {
+                "{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+"
+            }
+            Helper::Quotient => {
+                "{ Imparte fara rest cand ambele numere sunt intregi. }
+function cat(a, b: real): real;
+begin
+  if (frac(a) = 0) and (frac(b) = 0) then begin
+    cat := trunc(a / b);
+  end else begin
+    cat := a / b;
+  end;
+end;
+"
+            }
+        }
+    }
+}
+
+/// Writes the program in Pascal. Variables are declared with a type guessed
+/// from their values, and arrays with a named type, so they can be passed to
+/// subprograms. Strings are indexed from 1 in Pascal, so their indices are
+/// shifted by one. Reading a string reads a word, and reals are written with
+/// at most six decimals, as in the pseudocode.
+pub fn to_pascal(program: &[Statement]) -> String {
+    let types = Types::infer(program);
+    let subprograms = subprograms(program);
+    let mut pascal = Pascal {
+        types: &types,
+        scope: &types.main,
+        subprogram: None,
+        uses_math: false,
+        arrays: Vec::new(),
+        helpers: BTreeSet::new(),
+        read_after_loops: HashSet::new(),
+        out: Output::new("  "),
+    };
+
+    let definitions = definitions(program, &subprograms);
+
+    pascal.declarations(0);
+    if !pascal.out.text.is_empty() {
+        pascal.out.line("");
+    }
+    let globals = std::mem::take(&mut pascal.out.text);
+
+    // A subprogram that calls one defined after it needs it declared ahead.
+    let mut forward = HashSet::new();
+    for (position, subprogram) in definitions.iter().enumerate() {
+        let mut calls = HashSet::new();
+        called(&subprogram.block, &mut calls);
+        for later in &definitions[position + 1..] {
+            if calls.contains(later.name) {
+                forward.insert(later.name);
+            }
+        }
+    }
+    for subprogram in &definitions {
+        if forward.contains(subprogram.name) {
+            let header = pascal.header(subprogram);
+            pascal.out.line(&format!("{} forward;", header));
+        }
+    }
+    if !forward.is_empty() {
+        pascal.out.line("");
+    }
+    for subprogram in &definitions {
+        pascal.function(subprogram);
+        pascal.out.line("");
+    }
+
+    pascal.scope = &types.main;
+    pascal.subprogram = None;
+    pascal.read_after_loops.clear();
+    read_outside_loops(program, &mut pascal.read_after_loops);
+    pascal.out.line("begin");
+    pascal.nested(program);
+    pascal.out.line("end.");
+
+    let mut header = String::from("program pseudocod;\n\n");
+    if pascal.uses_math {
+        header += "uses math;\n\n";
+    }
+    if !pascal.arrays.is_empty() {
+        header += "type\n";
+        for &array in &pascal.arrays {
+            let base = base_type(array.base);
+            let bounds = if array.dimensions > 1 {
+                format!("0..{}, 0..{}", MATRIX_LENGTH - 1, MATRIX_LENGTH - 1)
+            } else {
+                format!("0..{}", ARRAY_LENGTH - 1)
+            };
+            header += &format!("  {} = array[{}] of {};\n", array_type(array), bounds, base);
+        }
+        header += "\n";
+    }
+    header += &globals;
+    for helper in &pascal.helpers {
+        header += helper.source();
+        header.push('\n');
+    }
+    header + &pascal.out.text
+}
+
+struct Pascal<'t, 'a> {
+    types: &'t Types<'a>,
+    scope: &'t Scope<'a>,
+    /// The subprogram being written, or `None` for the main program.
+    subprogram: Option<&'t Subprogram<'a>>,
+    /// Whether the program needs the `math` unit, for `floor`, `max` and `min`.
+    uses_math: bool,
+    /// The array types the program declares.
+    arrays: Vec<Type>,
+    helpers: BTreeSet<Helper>,
+    /// The variables of the scope read outside the `pentru` loops over them.
+    read_after_loops: HashSet<&'a str>,
+    out: Output,
+}
+
+fn base_type(base: Base) -> &'static str {
+    match base {
+        Base::Integer => "longint",
+        Base::Number | Base::Real => "real",
+        Base::Character => "char",
+        Base::Text => "string",
+    }
+}
+
+/// The name of an array type, such as `Vector` for an array of integers or
+/// `MatriceReal` for a matrix of reals.
+fn array_type(array: Type) -> String {
+    let name = if array.dimensions > 1 {
+        "Matrice"
+    } else {
+        "Vector"
+    };
+    match array.base {
+        Base::Integer => name.to_string(),
+        Base::Number | Base::Real => format!("{}Real", name),
+        Base::Character => format!("{}Char", name),
+        Base::Text => format!("{}String", name),
+    }
+}
+
+impl<'t, 'a> Pascal<'t, 'a> {
+    fn base_of(&self, expr: &Expression) -> Base {
+        self.types.of(self.scope, expr).base
+    }
+
+    fn type_name(&mut self, var_type: Type) -> String {
+        if var_type.dimensions == 0 {
+            return base_type(var_type.base).to_string();
+        }
+        // Numbers read from the input are reals, and share their arrays.
+        let base = match var_type.base {
+            Base::Number => Base::Real,
+            base => base,
+        };
+        let array = Type {
+            base,
+            dimensions: var_type.dimensions.min(2),
+        };
+        if !self.arrays.contains(&array) {
+            self.arrays.push(array);
+        }
+        array_type(array)
+    }
+
+    /// Groups consecutive variables of the same type, as in `a, b: longint`.
+    fn grouped(&mut self, variables: &[(&str, Type)]) -> Vec<String> {
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for &(name, var_type) in variables {
+            let type_name = self.type_name(var_type);
+            let name = identifier(name, RESERVED);
+            match groups.last_mut() {
+                Some((last, names)) if *last == type_name => names.push(name),
+                _ => groups.push((type_name, vec![name])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(type_name, names)| format!("{}: {}", names.join(", "), type_name))
+            .collect()
+    }
+
+    /// Declares the variables of the scope, skipping its parameters. Those of
+    /// the same type are declared together.
+    fn declarations(&mut self, parameters: usize) {
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for &(name, var_type) in &self.scope.variables[parameters..] {
+            let type_name = self.type_name(var_type);
+            let name = identifier(name, RESERVED);
+            match groups.iter_mut().find(|(group, _)| *group == type_name) {
+                Some((_, names)) => names.push(name),
+                None => groups.push((type_name, vec![name])),
+            }
+        }
+        if groups.is_empty() {
+            return;
+        }
+        self.out.line("var");
+        self.out.indent();
+        for (type_name, names) in &groups {
+            self.out
+                .line(&format!("{}: {};", names.join(", "), type_name));
+        }
+        self.out.dedent();
+    }
+
+    fn header(&mut self, subprogram: &Subprogram) -> String {
+        let scope = &self.types.subprograms[subprogram.name];
+        let parameters = self.grouped(&scope.variables[..subprogram.parameters.len()]);
+        let mut header = match self.types.results.get(subprogram.name) {
+            Some(_) => "function ",
+            None => "procedure ",
+        }
+        .to_string();
+        header += &identifier(subprogram.name, RESERVED);
+        if !parameters.is_empty() {
+            header += &format!("({})", parameters.join("; "));
+        }
+        if let Some(&result) = self.types.results.get(subprogram.name) {
+            header += &format!(": {}", self.type_name(result));
+        }
+        header + ";"
+    }
+
+    fn function(&mut self, subprogram: &'t Subprogram<'a>) {
+        self.scope = &self.types.subprograms[subprogram.name];
+        self.subprogram = Some(subprogram);
+        self.read_after_loops.clear();
+        read_outside_loops(&subprogram.block, &mut self.read_after_loops);
+        let header = self.header(subprogram);
+        self.out.line(&header);
+        self.declarations(subprogram.parameters.len());
+        self.out.line("begin");
+        self.nested(&subprogram.block);
+        self.out.line("end;");
+    }
+
+    fn block(&mut self, block: &[Statement<'a>]) {
+        for statement in block {
+            self.instruction(&statement.instruction);
+        }
+    }
+
+    fn nested(&mut self, block: &[Statement<'a>]) {
+        self.out.indent();
+        self.block(block);
+        self.out.dedent();
+    }
+
+    fn instruction(&mut self, instruction: &Instruction<'a>) {
+        match instruction {
+            // `read` fills a string with the rest of the line, so strings
+            // are read a word at a time by a procedure of their own.
+            Instruction::Read(targets) => {
+                let mut numbers = Vec::new();
+                for target in targets {
+                    let value = match target {
+                        LValue::Variable(var) => Expression::Variable(*var),
+                        LValue::Index(var, indices) => Expression::Index(*var, indices.clone()),
+                    };
+                    let is_text = self.types.of(self.scope, &value) == Type::scalar(Base::Text);
+                    let target = self.lvalue(target);
+                    if !is_text {
+                        numbers.push(target);
+                        continue;
+                    }
+                    if !numbers.is_empty() {
+                        self.out.line(&format!("read({});", numbers.join(", ")));
+                        numbers.clear();
+                    }
+                    self.helpers.insert(Helper::Read);
+                    self.out.line(&format!("citeste({});", target));
+                }
+                if !numbers.is_empty() {
+                    self.out.line(&format!("read({});", numbers.join(", ")));
+                }
+            }
+            Instruction::Write(writables, newline) => {
+                let items: Vec<String> = writables
+                    .iter()
+                    .map(|writable| match writable {
+                        Writable::Expression(expr) if self.types.of(self.scope, expr).is_real() => {
+                            self.helpers.insert(Helper::Text);
+                            format!("text({})", self.expression(expr, RELATIONAL))
+                        }
+                        Writable::Expression(expr) => self.expression(expr, RELATIONAL),
+                        Writable::String(text) => string_literal(&unescape(text)),
+                    })
+                    .collect();
+                let procedure = if *newline { "writeln" } else { "write" };
+                self.out
+                    .line(&format!("{}({});", procedure, items.join(", ")));
+            }
+            Instruction::Assignment(target, expr) => {
+                let target = self.lvalue(target);
+                let value = self.expression(expr, RELATIONAL);
+                self.out.line(&format!("{} := {};", target, value));
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                let cond = self.condition(cond, RELATIONAL);
+                self.out.line(&format!("if {} then begin", cond));
+                self.if_rest(if_block, else_block.as_deref());
+            }
+            Instruction::While(WhileType::While, cond, block) => {
+                let cond = self.condition(cond, RELATIONAL);
+                self.out.line(&format!("while {} do begin", cond));
+                self.nested(block);
+                self.out.line("end;");
+            }
+            Instruction::While(while_type, cond, block) => {
+                self.out.line("repeat");
+                self.nested(block);
+                let cond = if *while_type == WhileType::Repeat {
+                    self.condition(cond, RELATIONAL)
+                } else {
+                    self.negation(cond)
+                };
+                self.out.line(&format!("until {};", cond));
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => self.for_loop(variable, start_expr, end_expr, step, block),
+            Instruction::Call(name, arguments) => {
+                let call = self.call(name, arguments);
+                self.out.line(&format!("{};", call));
+            }
+            Instruction::Return(expr) => {
+                if let (Some(expr), Some(subprogram)) = (expr, self.subprogram) {
+                    let value = self.expression(expr, RELATIONAL);
+                    let name = identifier(subprogram.name, RESERVED);
+                    self.out.line(&format!("{} := {};", name, value));
+                    // The function ends anyway after its last instruction.
+                    if let Some(last) = subprogram.block.last() {
+                        if std::ptr::eq(&last.instruction, instruction) {
+                            return;
+                        }
+                    }
+                }
+                self.out.line("exit;");
+            }
+            Instruction::Subprogram(_) => {}
+        }
+    }
+
+    /// Closes an `if`, chaining a lone `daca` of its `altfel` as `else if`.
+    fn if_rest(&mut self, if_block: &[Statement<'a>], else_block: Option<&[Statement<'a>]>) {
+        self.nested(if_block);
+        match else_block {
+            Some([statement]) => match &statement.instruction {
+                Instruction::If(cond, if_block, else_block) => {
+                    let cond = self.condition(cond, RELATIONAL);
+                    self.out.line(&format!("end else if {} then begin", cond));
+                    self.if_rest(if_block, else_block.as_deref());
+                    return;
+                }
+                _ => {
+                    self.out.line("end else begin");
+                    self.nested(std::slice::from_ref(statement));
+                }
+            },
+            Some(else_block) => {
+                self.out.line("end else begin");
+                self.nested(else_block);
+            }
+            None => {}
+        }
+        self.out.line("end;");
+    }
+
+    /// Writes a `pentru` loop as a `for` loop when it counts through integers
+    /// one by one, and as a `while` loop otherwise, since the `for` loop of
+    /// Pascal only steps by one. The value of its variable after a `for` loop
+    /// is undefined, so a variable read after its loop is counted with
+    /// `while`.
+    fn for_loop(
+        &mut self,
+        variable: &'a str,
+        start_expr: &Expression<'a>,
+        end_expr: &Expression<'a>,
+        step: &Expression<'a>,
+        block: &[Statement<'a>],
+    ) {
+        let name = identifier(variable, RESERVED);
+        let integers = [start_expr, end_expr, step]
+            .iter()
+            .all(|expr| self.base_of(expr) == Base::Integer);
+        let start = self.expression(start_expr, RELATIONAL);
+        let end = self.expression(end_expr, ADDITIVE);
+        let counted = integers
+            && is_counted(variable, end_expr, block)
+            && !self.read_after_loops.contains(variable);
+        let (cond, next) = match constant_step(step) {
+            Some(Step::Ascending(size)) if counted && size == "1" => {
+                self.out
+                    .line(&format!("for {} := {} to {} do begin", name, start, end));
+                self.nested(block);
+                self.out.line("end;");
+                return;
+            }
+            Some(Step::Descending(size)) if counted && size == "1" => {
+                self.out.line(&format!(
+                    "for {} := {} downto {} do begin",
+                    name, start, end
+                ));
+                self.nested(block);
+                self.out.line("end;");
+                return;
+            }
+            Some(Step::Ascending(size)) => (
+                format!("{} <= {}", name, end),
+                format!("{} := {} + {};", name, name, size),
+            ),
+            Some(Step::Descending(size)) => (
+                format!("{} >= {}", name, end),
+                format!("{} := {} - {};", name, name, size),
+            ),
+            // The direction is only known once the step is computed.
+            None => {
+                let step = self.expression(step, MULTIPLICATIVE);
+                (
+                    format!(
+                        "(({} >= 0) and ({} <= {})) or (({} < 0) and ({} >= {}))",
+                        step, name, end, step, name, end
+                    ),
+                    format!("{} := {} + {};", name, name, step),
+                )
+            }
+        };
+        self.out.line(&format!("{} := {};", name, start));
+        self.out.line(&format!("while {} do begin", cond));
+        self.nested(block);
+        self.out.indent();
+        self.out.line(&next);
+        self.out.dedent();
+        self.out.line("end;");
+    }
+
+    fn lvalue(&mut self, target: &LValue<'a>) -> String {
+        match target {
+            LValue::Variable(var) => identifier(var, RESERVED),
+            LValue::Index(var, indices) => self.index(var, indices),
+        }
+    }
+
+    fn index(&mut self, var: &str, indices: &[Expression<'a>]) -> String {
+        let is_text = self
+            .scope
+            .get(var)
+            .is_some_and(|var_type| var_type.dimensions == 0);
+        let indices: Vec<String> = indices
+            .iter()
+            .map(|index| {
+                if is_text {
+                    self.expression(&shifted(index, 1), RELATIONAL)
+                } else {
+                    self.expression(index, RELATIONAL)
+                }
+            })
+            .collect();
+        format!("{}[{}]", identifier(var, RESERVED), indices.join(", "))
+    }
+
+    /// Writes `expr` as a condition. Numbers are true when they are not 0.
+    fn condition(&mut self, expr: &Expression<'a>, precedence: u8) -> String {
+        if is_boolean(expr) {
+            let (text, binding) = self.operation(expr);
+            parenthesized(text, binding, precedence)
+        } else {
+            let value = self.expression(expr, ADDITIVE);
+            parenthesized(format!("{} <> 0", value), RELATIONAL, precedence)
+        }
+    }
+
+    /// Writes the opposite of the condition `expr`, for the `until` of an
+    /// `executa ... cat timp` loop. Comparisons are turned around instead.
+    fn negation(&mut self, expr: &Expression<'a>) -> String {
+        match expr {
+            Expression::Order(order_type, expr1, expr2) => {
+                let (text, _) = self.binary(expr1, operator(order_type, true), expr2, RELATIONAL);
+                text
+            }
+            Expression::Not(expr) => self.condition(expr, RELATIONAL),
+            _ if !is_boolean(expr) => format!("{} = 0", self.expression(expr, ADDITIVE)),
+            _ => format!("not {}", self.condition(expr, UNARY)),
+        }
+    }
+
+    fn call(&mut self, name: &str, arguments: &[Expression<'a>]) -> String {
+        let is_subprogram = self.types.subprograms.contains_key(name);
+        let function = match name {
+            "sqrt" | "radical" if !is_subprogram => "sqrt".to_string(),
+            "abs" | "modul" if !is_subprogram => "abs".to_string(),
+            "lungime" if !is_subprogram => "length".to_string(),
+            // The `math` unit compares two values at a time.
+            "max" | "min" if !is_subprogram && !arguments.is_empty() => {
+                self.uses_math = true;
+                let mut result = self.expression(&arguments[0], RELATIONAL);
+                for argument in &arguments[1..] {
+                    let argument = self.expression(argument, RELATIONAL);
+                    result = format!("{}({}, {})", name, result, argument);
+                }
+                return result;
+            }
+            _ => identifier(name, RESERVED),
+        };
+        if arguments.is_empty() {
+            return function;
+        }
+        let arguments: Vec<String> = arguments
+            .iter()
+            .map(|argument| self.expression(argument, RELATIONAL))
+            .collect();
+        format!("{}({})", function, arguments.join(", "))
+    }
+}
+
+impl<'t, 'a> Infix<'a> for Pascal<'t, 'a> {
+    const OPERATORS: Operators = Operators {
+        or: ("or", ADDITIVE),
+        and: ("and", MULTIPLICATIVE),
+        not: ("not ", UNARY),
+        equal: "=",
+        not_equal: "<>",
+        equality: RELATIONAL,
+        relational: RELATIONAL,
+        additive: ADDITIVE,
+        multiplicative: MULTIPLICATIVE,
+        // A sign can only start an expression in Pascal, so a negative
+        // operand always gets parentheses.
+        minus: (ADDITIVE, UNARY),
+        grouped_comparisons: false,
+    };
+
+    /// Writes `expr` as a value. Comparisons give a boolean in Pascal, which
+    /// `ord` turns into 1 or 0, as in the pseudocode.
+    fn expression(&mut self, expr: &Expression<'a>, precedence: u8) -> String {
+        if is_boolean(expr) {
+            return format!("ord({})", self.condition(expr, RELATIONAL));
+        }
+        let (text, binding) = self.operation(expr);
+        parenthesized(text, binding, precedence)
+    }
+
+    fn operand(&mut self, expr: &Expression<'a>) -> (String, u8) {
+        match expr {
+            Expression::Constant(x) => (x.to_string(), PRIMARY),
+            Expression::RealConstant(x) => (format!("{:?}", x), PRIMARY),
+            Expression::StringConstant(text) => (string_literal(&unescape(text)), PRIMARY),
+            Expression::Variable(var) => (identifier(var, RESERVED), PRIMARY),
+            Expression::Index(var, indices) => (self.index(var, indices), PRIMARY),
+            Expression::IntegerPart(operand) => {
+                if self.base_of(operand) == Base::Integer {
+                    return self.operation(operand);
+                }
+                self.uses_math = true;
+                let operand = self.expression(operand, RELATIONAL);
+                (format!("floor({})", operand), PRIMARY)
+            }
+            Expression::Absolute(operand) => {
+                let operand = self.expression(operand, RELATIONAL);
+                (format!("abs({})", operand), PRIMARY)
+            }
+            Expression::Call(name, arguments) => (self.call(name, arguments), PRIMARY),
+            _ => self.operation(expr),
+        }
+    }
+
+    /// Integers are divided with `div` and `mod`, numbers read from the input
+    /// with `cat`, and the operands of `and`, `or` and `not` are conditions.
+    fn special(&mut self, expr: &Expression<'a>) -> Option<(String, u8)> {
+        match expr {
+            Expression::Division(expr1, expr2) if self.base_of(expr) == Base::Number => {
+                self.helpers.insert(Helper::Quotient);
+                let text1 = self.expression(expr1, RELATIONAL);
+                let text2 = self.expression(expr2, RELATIONAL);
+                Some((format!("cat({}, {})", text1, text2), PRIMARY))
+            }
+            Expression::Division(expr1, expr2) if self.base_of(expr) != Base::Real => {
+                Some(self.binary(expr1, "div", expr2, MULTIPLICATIVE))
+            }
+            Expression::Reminder(expr1, expr2) => {
+                Some(self.binary(expr1, "mod", expr2, MULTIPLICATIVE))
+            }
+            Expression::And(expr1, expr2) => {
+                let text1 = self.condition(expr1, MULTIPLICATIVE);
+                let text2 = self.condition(expr2, MULTIPLICATIVE + 1);
+                Some((format!("{} and {}", text1, text2), MULTIPLICATIVE))
+            }
+            Expression::Or(expr1, expr2) => {
+                let text1 = self.condition(expr1, ADDITIVE);
+                let text2 = self.condition(expr2, ADDITIVE + 1);
+                Some((format!("{} or {}", text1, text2), ADDITIVE))
+            }
+            Expression::Not(expr) => Some((format!("not {}", self.condition(expr, UNARY)), UNARY)),
+            _ => None,
+        }
+    }
+}
+
+/// The Pascal operator of a comparison, or of its opposite.
+fn operator(order_type: &OrderType, negated: bool) -> &'static str {
+    match (order_type, negated) {
+        (OrderType::Equal, false) | (OrderType::NotEqual, true) => "=",
+        (OrderType::NotEqual, false) | (OrderType::Equal, true) => "<>",
+        (OrderType::Less, false) | (OrderType::GreaterOrEqual, true) => "<",
+        (OrderType::LessOrEqual, false) | (OrderType::Greater, true) => "<=",
+        (OrderType::Greater, false) | (OrderType::LessOrEqual, true) => ">",
+        (OrderType::GreaterOrEqual, false) | (OrderType::Less, true) => ">=",
+    }
+}
+
+/// Whether `expr` gives a boolean in Pascal.
+fn is_boolean(expr: &Expression) -> bool {
+    matches!(
+        expr,
+        Expression::Order(_, _, _)
+            | Expression::And(_, _)
+            | Expression::Or(_, _)
+            | Expression::Not(_)
+    )
+}
+
+/// Quotes text the way Pascal does: a quote is doubled, and characters that
+/// cannot be typed, such as a newline, are written by their code, as in
+/// `'linia 1'#10'linia 2'`.
+fn string_literal(text: &str) -> String {
+    let mut literal = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        if c.is_control() {
+            if quoted {
+                literal.push('\'');
+                quoted = false;
+            }
+            literal += &format!("#{}", c as u32);
+        } else {
+            if !quoted {
+                literal.push('\'');
+                quoted = true;
+            }
+            if c == '\'' {
+                literal.push('\'');
+            }
+            literal.push(c);
+        }
+    }
+    if quoted || literal.is_empty() {
+        if !quoted {
+            literal.push('\'');
+        }
+        literal.push('\'');
+    }
+    literal
+}
@@ -0,0 +1,314 @@
+use super::{Infix, Operators, Output};
+use crate::frontend::expression::{Expression, LValue};
+use crate::frontend::instruction::{Instruction, Statement, WhileType, Writable};
+
+/// How tightly the pseudocode operators bind, from the loosest to the
+/// tightest, following the grammar of the parser.
+const OR: u8 = 1;
+const AND: u8 = 2;
+const NOT: u8 = 3;
+const COMPARISON: u8 = 4;
+const MEMBER: u8 = 5;
+const TERM: u8 = 6;
+const FACTOR: u8 = 7;
+
+/// Writes a parsed program back as pseudocode, with its blocks indented by
+/// two spaces. Comments are not part of the parsed program, so they are lost.
+pub fn to_pseudocode(program: &[Statement]) -> String {
+    let mut pseudocode = Pseudocode {
+        out: Output::new("  "),
+    };
+    let is_subprogram =
+        |statement: &Statement| matches!(statement.instruction, Instruction::Subprogram(_));
+    for (position, statement) in program.iter().enumerate() {
+        // Subprograms are set apart from what surrounds them.
+        if position > 0 && (is_subprogram(statement) || is_subprogram(&program[position - 1])) {
+            pseudocode.out.line("");
+        }
+        pseudocode.instruction(&statement.instruction);
+    }
+    pseudocode.out.text
+}
+
+struct Pseudocode {
+    out: Output,
+}
+
+impl Pseudocode {
+    fn lvalue(&mut self, target: &LValue) -> String {
+        match target {
+            LValue::Variable(var) => var.to_string(),
+            LValue::Index(var, indices) => self.index(var, indices),
+        }
+    }
+
+    fn index(&mut self, var: &str, indices: &[Expression]) -> String {
+        let indices: String = indices
+            .iter()
+            .map(|index| format!("[{}]", self.expression(index, OR)))
+            .collect();
+        format!("{}{}", var, indices)
+    }
+
+    fn call(&mut self, name: &str, arguments: &[Expression]) -> String {
+        let arguments: Vec<String> = arguments
+            .iter()
+            .map(|argument| self.expression(argument, OR))
+            .collect();
+        format!("{}({})", name, arguments.join(", "))
+    }
+
+    fn nested(&mut self, block: &[Statement]) {
+        self.out.indent();
+        for statement in block {
+            self.instruction(&statement.instruction);
+        }
+        self.out.dedent();
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::Read(targets) => {
+                let targets: Vec<String> =
+                    targets.iter().map(|target| self.lvalue(target)).collect();
+                self.out.line(&format!("citeste {}", targets.join(", ")));
+            }
+            Instruction::Write(writables, newline) => {
+                let items: Vec<String> = writables
+                    .iter()
+                    .map(|writable| match writable {
+                        Writable::String(text) => quoted(text),
+                        // A lone text would be read back as text to write, so
+                        // it keeps its parentheses.
+                        Writable::Expression(expr @ Expression::StringConstant(_)) => {
+                            format!("({})", self.expression(expr, OR))
+                        }
+                        Writable::Expression(expr) => self.expression(expr, OR),
+                    })
+                    .collect();
+                let comma = if *newline { "" } else { "," };
+                self.out
+                    .line(&format!("scrie {}{}", items.join(", "), comma));
+            }
+            Instruction::Assignment(target, expr) => {
+                let target = self.lvalue(target);
+                let expr = self.expression(expr, OR);
+                self.out.line(&format!("{} <- {}", target, expr));
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("daca {} atunci", cond));
+                self.nested(if_block);
+                if let Some(else_block) = else_block {
+                    self.out.line("altfel");
+                    self.nested(else_block);
+                }
+            }
+            Instruction::While(WhileType::While, cond, block) => {
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("cat timp {} executa", cond));
+                self.nested(block);
+            }
+            Instruction::While(WhileType::DoWhile, cond, block) => {
+                self.out.line("executa");
+                self.nested(block);
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("cat timp {}", cond));
+            }
+            Instruction::While(WhileType::Repeat, cond, block) => {
+                self.out.line("repeta");
+                self.nested(block);
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("pana cand {}", cond));
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => {
+                let start = self.expression(start_expr, OR);
+                let end = self.expression(end_expr, OR);
+                let mut header = format!("pentru {} <- {}, {}", variable, start, end);
+                if *step != Expression::Constant(1) {
+                    header += &format!(", {}", self.expression(step, OR));
+                }
+                self.out.line(&format!("{} executa", header));
+                self.nested(block);
+            }
+            Instruction::Call(name, arguments) => {
+                let call = self.call(name, arguments);
+                self.out.line(&call)
+            }
+            Instruction::Return(None) => self.out.line("returneaza"),
+            Instruction::Return(Some(expr)) => {
+                let expr = self.expression(expr, OR);
+                self.out.line(&format!("returneaza {}", expr));
+            }
+            Instruction::Subprogram(subprogram) => {
+                let keyword = if returns_value(&subprogram.block) {
+                    "functie"
+                } else {
+                    "subprogram"
+                };
+                self.out.line(&format!(
+                    "{} {}({})",
+                    keyword,
+                    subprogram.name,
+                    subprogram.parameters.join(", ")
+                ));
+                self.nested(&subprogram.block);
+            }
+        }
+    }
+}
+
+impl<'a> Infix<'a> for Pseudocode {
+    const OPERATORS: Operators = Operators {
+        or: ("sau", OR),
+        and: ("si", AND),
+        not: ("not ", NOT),
+        equal: "=",
+        not_equal: "<>",
+        equality: COMPARISON,
+        relational: COMPARISON,
+        additive: MEMBER,
+        multiplicative: TERM,
+        // A minus sign can only start a sum, so it binds as loosely as one.
+        minus: (MEMBER, TERM),
+        grouped_comparisons: false,
+    };
+
+    fn operand(&mut self, expr: &Expression<'a>) -> (String, u8) {
+        match expr {
+            Expression::Constant(x) => (x.to_string(), FACTOR),
+            Expression::RealConstant(x) => (real(*x), FACTOR),
+            Expression::StringConstant(text) => (quoted(text), FACTOR),
+            Expression::Variable(var) => (var.to_string(), FACTOR),
+            Expression::Index(var, indices) => (self.index(var, indices), FACTOR),
+            Expression::IntegerPart(expr) => (format!("[{}]", self.expression(expr, OR)), FACTOR),
+            Expression::Absolute(expr) => (format!("|{}|", self.expression(expr, OR)), FACTOR),
+            Expression::Call(name, arguments) => (self.call(name, arguments), FACTOR),
+            _ => self.operation(expr),
+        }
+    }
+}
+
+/// Whether a subprogram gives a value back, so it is written as a `functie`.
+fn returns_value(block: &[Statement]) -> bool {
+    block.iter().any(|statement| match &statement.instruction {
+        Instruction::Return(expr) => expr.is_some(),
+        Instruction::If(_, if_block, else_block) => {
+            returns_value(if_block) || else_block.as_deref().is_some_and(returns_value)
+        }
+        Instruction::While(_, _, block) | Instruction::For { block, .. } => returns_value(block),
+        _ => false,
+    })
+}
+
+/// Writes a real so that it is read back as the same real: with a decimal
+/// point and without an exponent.
+fn real(x: f64) -> String {
+    let text = x.to_string();
+    if text.contains('.') {
+        text
+    } else {
+        text + ".0"
+    }
+}
+
+/// Quotes text as it was written, escape sequences included. Single quotes
+/// are used, unless the text holds one that is not escaped.
+fn quoted(text: &str) -> String {
+    let mut escaped = false;
+    let mut quote = '\'';
+    for c in text.chars() {
+        if !escaped && c == '\'' {
+            quote = '"';
+        }
+        escaped = !escaped && c == '\\';
+    }
+    format!("{}{}{}", quote, text, quote)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frontend::instruction::{program_with, Block, Layout, DEFAULT_TAB_WIDTH};
+    use crate::{parse, Options};
+    use nom::error::Error;
+
+    /// Formats a program, checking that it is read back as the same program.
+    fn round_trip(source: &str) -> String {
+        let program = parse(source, &Options::default()).unwrap();
+        let formatted = to_pseudocode(&program);
+        let reparsed: Result<(_, Block), Error<_>> =
+            program_with(&formatted, Layout::Indentation, DEFAULT_TAB_WIDTH);
+        assert_eq!(reparsed.unwrap().1, program, "{}", formatted);
+        formatted
+    }
+
+    #[test]
+    fn resources_round_trip_test() {
+        let resources = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("resources");
+        for entry in std::fs::read_dir(resources).unwrap() {
+            let path = entry.unwrap().path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "pseudo")
+            {
+                let source = std::fs::read_to_string(&path).unwrap();
+                let formatted = round_trip(&source);
+                assert_eq!(round_trip(&formatted), formatted, "{}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn parentheses_test() {
+        assert_eq!(
+            round_trip("x<-(a+b)*(-c)-(d-e)"),
+            "x <- (a + b) * (-c) - (d - e)\n"
+        );
+        assert_eq!(
+            round_trip("x <- -(a + b) + (-a * b)"),
+            "x <- -(a + b) + (-a * b)\n"
+        );
+        assert_eq!(round_trip("x <- (-a) * b - (-1)"), "x <- (-a) * b - (-1)\n");
+        assert_eq!(round_trip("x <- ((a % b) / c) * d"), "x <- a % b / c * d\n");
+        assert_eq!(round_trip("x <- a / (b * c)"), "x <- a / (b * c)\n");
+        assert_eq!(
+            round_trip("daca (a < b) = (c <> d) sau not (x si y) atunci\n  scrie [x / 2], |-y|"),
+            "daca a < b = (c <> d) sau not (x si y) atunci\n  scrie [x / 2], |-y|\n"
+        );
+        assert_eq!(
+            round_trip("daca (a sau b) si c atunci\n  f((a))"),
+            "daca (a sau b) si c atunci\n  f(a)\n"
+        );
+        assert_eq!(
+            round_trip("daca a sau b si c atunci\n  scrie (a sau b) si c"),
+            "daca a sau b si c atunci\n  scrie (a sau b) si c\n"
+        );
+        assert_eq!(
+            round_trip("scrie not not x, -2.5"),
+            "scrie not not x, -2.5\n"
+        );
+    }
+
+    #[test]
+    fn instructions_test() {
+        assert_eq!(
+            round_trip("pentru i<-1,n executa\n  scrie ('a'), \"it's\", v[i][j],\nreturneaza"),
+            "pentru i <- 1, n executa\n  scrie ('a'), \"it's\", v[i][j],\nreturneaza\n"
+        );
+        assert_eq!(
+            round_trip(
+                "functie f(a, b)\n  repeta\n    a <- a - 1\n  pana cand a<b\n  returneaza a\nsubprogram g()\n  executa\n    citeste x, v[1]\n  cat timp x\nscrie f(3, 1)"
+            ),
+            "functie f(a, b)\n  repeta\n    a <- a - 1\n  pana cand a < b\n  returneaza a\n\nsubprogram g()\n  executa\n    citeste x, v[1]\n  cat timp x\n\nscrie f(3, 1)\n"
+        );
+    }
+}
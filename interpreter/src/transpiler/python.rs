@@ -0,0 +1,563 @@
+use std::collections::{BTreeSet, HashSet};
+
+use super::types::{definitions, subprograms, Base, Scope, Types};
+use super::{
+    assigned, constant_step, identifier, is_counted, read_outside_loops, shifted, Infix, Operators,
+    Output, Step,
+};
+use crate::backend::unescape;
+use crate::frontend::expression::{Expression, LValue};
+use crate::frontend::instruction::{Instruction, Statement, Subprogram, WhileType, Writable};
+
+const RESERVED: &[&str] = &[
+    "abs", "and", "as", "assert", "async", "await", "bool", "break", "cat", "citeste", "class",
+    "continue", "def", "del", "dict", "elif", "else", "except", "false", "finally", "float", "for",
+    "from", "global", "if", "import", "in", "input", "int", "is", "lambda", "len", "list", "math",
+    "max", "min", "none", "nonlocal", "not", "or", "pass", "print", "raise", "range", "rest",
+    "return", "str", "sum", "sys", "text", "true", "try", "while", "with", "yield",
+];
+
+/// How tightly the Python operators bind, from the loosest to the tightest.
+const OR: u8 = 1;
+const AND: u8 = 2;
+const NOT: u8 = 3;
+const COMPARISON: u8 = 4;
+const ADDITIVE: u8 = 5;
+const MULTIPLICATIVE: u8 = 6;
+const UNARY: u8 = 7;
+const PRIMARY: u8 = 8;
+
+/// Functions the generated program defines for the pseudocode operations
+/// Python does differently.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Helper {
+    Read,
+    Text,
+    Quotient,
+    Remainder,
+}
+
+impl Helper {
+    fn source(self) -> &'static str {
+        match self {
+            Helper::Read => {
+                r#"def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+"#
+            }
+            Helper::Text => {
+                r#"def text(valoare):
+    """Scrie un numar real cu cel mult sase zecimale."""
+    valoare = f"{valoare:.6f}".rstrip("0").rstrip(".")
+    return "0" if valoare == "-0" else valoare
+"#
+            }
+            Helper::Quotient => {
+                r#"def cat(a, b):
+    """Imparte doi intregi fara rest, rotunjind spre zero."""
+    if isinstance(a, int) and isinstance(b, int):
+        q = abs(a) // abs(b)
+        return q if (a < 0) == (b < 0) else -q
+    return a / b
+"#
+            }
+            Helper::Remainder => {
+                r#"def rest(a, b):
+    """Restul impartirii a doi intregi, cu semnul deimpartitului."""
+    return a - b * cat(a, b)
+"#
+            }
+        }
+    }
+}
+
+/// Writes the program in Python, keeping the meaning of the pseudocode where
+/// the two languages differ: integer division and remainder round towards
+/// zero, `citeste` reads the next word of the input, and reals are written
+/// with at most six decimals. Arrays become dictionaries, indexed by a tuple
+/// when they have several dimensions. Integers are not limited to 32 bits.
+pub fn to_python(program: &[Statement]) -> String {
+    let types = Types::infer(program);
+    let subprograms = subprograms(program);
+    let mut python = Python {
+        types: &types,
+        scope: &types.main,
+        in_subprogram: false,
+        read_after_loops: HashSet::new(),
+        imports: BTreeSet::new(),
+        helpers: BTreeSet::new(),
+        out: Output::new("    "),
+    };
+
+    let mut functions = Vec::new();
+    for subprogram in definitions(program, &subprograms) {
+        python.function(subprogram);
+        functions.push(std::mem::take(&mut python.out.text));
+    }
+    python.scope = &types.main;
+    python.in_subprogram = false;
+    python.read_after_loops.clear();
+    read_outside_loops(program, &mut python.read_after_loops);
+    python.arrays(0);
+    python.block(program);
+
+    let mut sections = Vec::new();
+    if !python.imports.is_empty() {
+        let imports: Vec<String> = python
+            .imports
+            .iter()
+            .map(|module| format!("import {}\n", module))
+            .collect();
+        sections.push(imports.concat());
+    }
+    sections.extend(
+        python
+            .helpers
+            .iter()
+            .map(|helper| helper.source().to_string()),
+    );
+    sections.extend(functions);
+    sections.push(python.out.text);
+    sections.join("\n\n")
+}
+
+struct Python<'t, 'a> {
+    types: &'t Types<'a>,
+    scope: &'t Scope<'a>,
+    in_subprogram: bool,
+    /// The variables of the scope read outside the `pentru` loops over them.
+    read_after_loops: HashSet<&'a str>,
+    /// The modules the program needs.
+    imports: BTreeSet<&'static str>,
+    helpers: BTreeSet<Helper>,
+    out: Output,
+}
+
+impl<'t, 'a> Python<'t, 'a> {
+    fn base_of(&self, expr: &Expression) -> Base {
+        self.types.of(self.scope, expr).base
+    }
+
+    fn helper(&mut self, helper: Helper) {
+        match helper {
+            Helper::Read => {
+                self.imports.insert("sys");
+            }
+            Helper::Remainder => {
+                self.helpers.insert(Helper::Quotient);
+            }
+            Helper::Text | Helper::Quotient => {}
+        }
+        self.helpers.insert(helper);
+    }
+
+    /// Whether `var` holds text, whose characters are changed by index.
+    fn is_text(&self, var: &str) -> bool {
+        self.scope
+            .get(var)
+            .is_some_and(|var_type| var_type.dimensions == 0)
+    }
+
+    fn function(&mut self, subprogram: &'t Subprogram<'a>) {
+        self.scope = &self.types.subprograms[subprogram.name];
+        self.in_subprogram = true;
+        self.read_after_loops.clear();
+        read_outside_loops(&subprogram.block, &mut self.read_after_loops);
+        let parameters: Vec<String> = subprogram
+            .parameters
+            .iter()
+            .map(|parameter| identifier(parameter, RESERVED))
+            .collect();
+        self.out.line(&format!(
+            "def {}({}):",
+            identifier(subprogram.name, RESERVED),
+            parameters.join(", ")
+        ));
+        self.out.indent();
+        // Arrays are passed by reference, so changing one changes a copy.
+        let mut changed = HashSet::new();
+        assigned(&subprogram.block, &mut changed);
+        for &(name, var_type) in &self.scope.variables[..subprogram.parameters.len()] {
+            if var_type.dimensions > 0 && changed.contains(name) {
+                let name = identifier(name, RESERVED);
+                self.out.line(&format!("{} = dict({})", name, name));
+            }
+        }
+        self.arrays(subprogram.parameters.len());
+        self.block(&subprogram.block);
+        self.out.dedent();
+    }
+
+    /// Creates the arrays of the scope, skipping its parameters.
+    fn arrays(&mut self, parameters: usize) {
+        for &(name, var_type) in &self.scope.variables[parameters..] {
+            if var_type.dimensions > 0 {
+                self.out
+                    .line(&format!("{} = {{}}", identifier(name, RESERVED)));
+            }
+        }
+    }
+
+    fn block(&mut self, block: &[Statement<'a>]) {
+        for statement in block {
+            self.instruction(&statement.instruction);
+        }
+    }
+
+    /// Writes an indented block, which Python does not allow to be empty.
+    fn nested(&mut self, block: &[Statement<'a>]) {
+        self.out.indent();
+        let length = self.out.text.len();
+        self.block(block);
+        if self.out.text.len() == length {
+            self.out.line("pass");
+        }
+        self.out.dedent();
+    }
+
+    fn instruction(&mut self, instruction: &Instruction<'a>) {
+        match instruction {
+            Instruction::Read(targets) => {
+                self.helper(Helper::Read);
+                for target in targets {
+                    self.assign(target, "citeste()".to_string());
+                }
+            }
+            Instruction::Write(writables, newline) => {
+                let mut arguments: Vec<String> = writables
+                    .iter()
+                    .map(|writable| match writable {
+                        Writable::Expression(expr) => self.written(expr),
+                        Writable::String(text) => string_literal(&unescape(text)),
+                    })
+                    .collect();
+                if arguments.len() > 1 {
+                    arguments.push("sep=\"\"".to_string());
+                }
+                if !newline {
+                    arguments.push("end=\"\"".to_string());
+                }
+                self.out.line(&format!("print({})", arguments.join(", ")));
+            }
+            Instruction::Assignment(target, expr) => {
+                let value = self.expression(expr, OR);
+                self.assign(target, value);
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("if {}:", cond));
+                self.if_rest(if_block, else_block.as_deref());
+            }
+            Instruction::While(WhileType::While, cond, block) => {
+                let cond = self.expression(cond, OR);
+                self.out.line(&format!("while {}:", cond));
+                self.nested(block);
+            }
+            Instruction::While(while_type, cond, block) => {
+                self.out.line("while True:");
+                self.nested(block);
+                let cond = if *while_type == WhileType::Repeat {
+                    self.expression(cond, OR)
+                } else {
+                    self.negation(cond)
+                };
+                self.out.indent();
+                self.out.line(&format!("if {}:", cond));
+                self.out.indent();
+                self.out.line("break");
+                self.out.dedent();
+                self.out.dedent();
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => self.for_loop(variable, start_expr, end_expr, step, block),
+            Instruction::Call(name, arguments) => {
+                let call = self.call(name, arguments);
+                self.out.line(&call);
+            }
+            Instruction::Return(expr) => match expr {
+                Some(expr) if self.in_subprogram => {
+                    let value = self.expression(expr, OR);
+                    self.out.line(&format!("return {}", value));
+                }
+                _ if self.in_subprogram => self.out.line("return"),
+                _ => {
+                    self.imports.insert("sys");
+                    self.out.line("sys.exit()");
+                }
+            },
+            Instruction::Subprogram(_) => {}
+        }
+    }
+
+    /// Closes an `if`, chaining a lone `daca` of its `altfel` as `elif`.
+    fn if_rest(&mut self, if_block: &[Statement<'a>], else_block: Option<&[Statement<'a>]>) {
+        self.nested(if_block);
+        match else_block {
+            Some([statement]) => match &statement.instruction {
+                Instruction::If(cond, if_block, else_block) => {
+                    let cond = self.expression(cond, OR);
+                    self.out.line(&format!("elif {}:", cond));
+                    self.if_rest(if_block, else_block.as_deref());
+                }
+                _ => {
+                    self.out.line("else:");
+                    self.nested(std::slice::from_ref(statement));
+                }
+            },
+            Some(else_block) => {
+                self.out.line("else:");
+                self.nested(else_block);
+            }
+            None => {}
+        }
+    }
+
+    /// Writes a `pentru` loop as a loop over a `range` when it counts through
+    /// integers, and as a `while` loop otherwise. The end of the range is
+    /// past the last value, so `pentru i <- 1, n` loops over `range(1, n + 1)`.
+    /// After a `range`, the variable keeps the last value instead of going one
+    /// step past the end, so a variable read after its loop is counted with
+    /// `while`.
+    fn for_loop(
+        &mut self,
+        variable: &'a str,
+        start_expr: &Expression<'a>,
+        end_expr: &Expression<'a>,
+        step: &Expression<'a>,
+        block: &[Statement<'a>],
+    ) {
+        let name = identifier(variable, RESERVED);
+        let integers = [start_expr, end_expr, step]
+            .iter()
+            .all(|expr| self.base_of(expr) == Base::Integer);
+        let start = self.expression(start_expr, OR);
+        match constant_step(step) {
+            Some(step)
+                if integers
+                    && is_counted(variable, end_expr, block)
+                    && !self.read_after_loops.contains(variable) =>
+            {
+                let range = match step {
+                    Step::Ascending(size) if size == "1" => {
+                        format!("{}, {}", start, self.shifted(end_expr, 1))
+                    }
+                    Step::Ascending(size) => {
+                        format!("{}, {}, {}", start, self.shifted(end_expr, 1), size)
+                    }
+                    Step::Descending(size) => {
+                        format!("{}, {}, -{}", start, self.shifted(end_expr, -1), size)
+                    }
+                };
+                self.out.line(&format!("for {} in range({}):", name, range));
+                self.nested(block);
+            }
+            step_sign => {
+                let end = self.expression(end_expr, COMPARISON + 1);
+                let (cond, next) = match step_sign {
+                    Some(Step::Ascending(size)) => (
+                        format!("{} <= {}", name, end),
+                        format!("{} += {}", name, size),
+                    ),
+                    Some(Step::Descending(size)) => (
+                        format!("{} >= {}", name, end),
+                        format!("{} -= {}", name, size),
+                    ),
+                    // The direction is only known once the step is computed.
+                    None => {
+                        let step = self.expression(step, COMPARISON + 1);
+                        (
+                            format!(
+                                "({} <= {} if {} >= 0 else {} >= {})",
+                                name, end, step, name, end
+                            ),
+                            format!("{} += {}", name, step),
+                        )
+                    }
+                };
+                self.out.line(&format!("{} = {}", name, start));
+                self.out.line(&format!("while {}:", cond));
+                self.nested(block);
+                self.out.indent();
+                self.out.line(&next);
+                self.out.dedent();
+            }
+        }
+    }
+
+    fn shifted(&mut self, expr: &Expression<'a>, delta: i32) -> String {
+        self.expression(&shifted(expr, delta), OR)
+    }
+
+    fn assign(&mut self, target: &LValue<'a>, value: String) {
+        match target {
+            LValue::Variable(var) => {
+                self.out
+                    .line(&format!("{} = {}", identifier(var, RESERVED), value));
+            }
+            // Python strings cannot be changed, so a new one replaces it.
+            LValue::Index(var, indices) if self.is_text(var) => {
+                let var = identifier(var, RESERVED);
+                let index = self.expression(&indices[0], OR);
+                let next = self.shifted(&indices[0], 1);
+                self.out.line(&format!(
+                    "{} = {}[:{}] + {} + {}[{}:]",
+                    var, var, index, value, var, next
+                ));
+            }
+            LValue::Index(var, indices) => {
+                let index = self.index(var, indices);
+                self.out.line(&format!("{} = {}", index, value));
+            }
+        }
+    }
+
+    fn index(&mut self, var: &str, indices: &[Expression<'a>]) -> String {
+        let indices: Vec<String> = indices
+            .iter()
+            .map(|index| self.expression(index, OR))
+            .collect();
+        format!("{}[{}]", identifier(var, RESERVED), indices.join(", "))
+    }
+
+    /// Writes a value the way the pseudocode shows it: comparisons as 1 or 0,
+    /// and reals with at most six decimals.
+    fn written(&mut self, expr: &Expression<'a>) -> String {
+        match expr {
+            Expression::Order(_, _, _) | Expression::Not(_) => {
+                format!("int({})", self.expression(expr, OR))
+            }
+            _ if self.base_of(expr) == Base::Real => {
+                self.helper(Helper::Text);
+                format!("text({})", self.expression(expr, OR))
+            }
+            _ => self.expression(expr, OR),
+        }
+    }
+
+    fn negation(&mut self, expr: &Expression<'a>) -> String {
+        format!("not {}", self.expression(expr, NOT))
+    }
+
+    fn helper_call(
+        &mut self,
+        name: &str,
+        expr1: &Expression<'a>,
+        expr2: &Expression<'a>,
+    ) -> String {
+        let text1 = self.expression(expr1, OR);
+        let text2 = self.expression(expr2, OR);
+        format!("{}({}, {})", name, text1, text2)
+    }
+
+    fn call(&mut self, name: &str, arguments: &[Expression<'a>]) -> String {
+        let function = match name {
+            _ if self.types.subprograms.contains_key(name) => identifier(name, RESERVED),
+            "sqrt" | "radical" => {
+                self.imports.insert("math");
+                "math.sqrt".to_string()
+            }
+            "abs" | "modul" => "abs".to_string(),
+            "lungime" => "len".to_string(),
+            "max" | "min" => name.to_string(),
+            _ => identifier(name, RESERVED),
+        };
+        let arguments: Vec<String> = arguments
+            .iter()
+            .map(|argument| self.expression(argument, OR))
+            .collect();
+        format!("{}({})", function, arguments.join(", "))
+    }
+}
+
+impl<'t, 'a> Infix<'a> for Python<'t, 'a> {
+    const OPERATORS: Operators = Operators {
+        or: ("or", OR),
+        and: ("and", AND),
+        not: ("not ", NOT),
+        equal: "==",
+        not_equal: "!=",
+        equality: COMPARISON,
+        relational: COMPARISON,
+        additive: ADDITIVE,
+        multiplicative: MULTIPLICATIVE,
+        minus: (UNARY, UNARY),
+        // Python chains comparisons, so `a < b < c` would mean
+        // `a < b and b < c`.
+        grouped_comparisons: true,
+    };
+
+    fn operand(&mut self, expr: &Expression<'a>) -> (String, u8) {
+        match expr {
+            Expression::Constant(x) => (x.to_string(), PRIMARY),
+            Expression::RealConstant(x) => (format!("{:?}", x), PRIMARY),
+            Expression::StringConstant(text) => (string_literal(&unescape(text)), PRIMARY),
+            Expression::Variable(var) => (identifier(var, RESERVED), PRIMARY),
+            Expression::Index(var, indices) => (self.index(var, indices), PRIMARY),
+            Expression::IntegerPart(operand) => {
+                if let Expression::Constant(_) = **operand {
+                    return self.operation(operand);
+                }
+                self.imports.insert("math");
+                let operand = self.expression(operand, OR);
+                (format!("math.floor({})", operand), PRIMARY)
+            }
+            Expression::Absolute(operand) => {
+                (format!("abs({})", self.expression(operand, OR)), PRIMARY)
+            }
+            Expression::Call(name, arguments) => (self.call(name, arguments), PRIMARY),
+            _ => self.operation(expr),
+        }
+    }
+
+    /// Integer division and remainder round towards zero in the pseudocode.
+    fn special(&mut self, expr: &Expression<'a>) -> Option<(String, u8)> {
+        match expr {
+            Expression::Division(expr1, expr2) if self.base_of(expr) != Base::Real => {
+                self.helper(Helper::Quotient);
+                Some((self.helper_call("cat", expr1, expr2), PRIMARY))
+            }
+            Expression::Reminder(expr1, expr2) => {
+                self.helper(Helper::Remainder);
+                Some((self.helper_call("rest", expr1, expr2), PRIMARY))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn string_literal(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\n' => literal += "\\n",
+            '\t' => literal += "\\t",
+            '\\' => literal += "\\\\",
+            '"' => literal += "\\\"",
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
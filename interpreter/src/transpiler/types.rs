@@ -0,0 +1,610 @@
+use std::collections::{HashMap, HashSet};
+
+use super::variables;
+use crate::backend::unescape;
+use crate::frontend::expression::{Expression, LValue};
+use crate::frontend::instruction::{Instruction, Statement, Subprogram, Writable};
+
+/// The kinds of values, from the narrowest to the widest. A variable that is
+/// given values of several kinds takes the widest of them.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum Base {
+    Integer,
+    /// A number read from the input, which may be an integer or a real.
+    Number,
+    Real,
+    Character,
+    Text,
+}
+
+/// The type of a variable: an array has one dimension for each of its
+/// indices, and any other value has none.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Type {
+    pub base: Base,
+    pub dimensions: usize,
+}
+
+impl Type {
+    pub const INTEGER: Type = Type::scalar(Base::Integer);
+
+    pub const fn scalar(base: Base) -> Self {
+        Type {
+            base,
+            dimensions: 0,
+        }
+    }
+
+    /// Whether the values are held as reals, even those that are integers.
+    pub fn is_real(&self) -> bool {
+        self.dimensions == 0 && matches!(self.base, Base::Number | Base::Real)
+    }
+
+    pub fn is_textual(&self) -> bool {
+        self.dimensions == 0 && self.base >= Base::Character
+    }
+
+    fn widest(self, other: Type) -> Type {
+        Type {
+            base: self.base.max(other.base),
+            dimensions: self.dimensions.max(other.dimensions),
+        }
+    }
+}
+
+/// The variables of the main program or of a subprogram.
+#[derive(Default, Debug)]
+pub struct Scope<'a> {
+    /// The variables in the order they first appear, starting with the
+    /// parameters of a subprogram.
+    pub variables: Vec<(&'a str, Type)>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn get(&self, name: &str) -> Option<Type> {
+        self.variables
+            .iter()
+            .find(|(variable, _)| *variable == name)
+            .map(|&(_, var_type)| var_type)
+    }
+
+    /// Records that `name` holds values of `var_type`, returning whether this
+    /// changed what was known about it.
+    fn widen(&mut self, name: &'a str, var_type: Type) -> bool {
+        match self
+            .variables
+            .iter_mut()
+            .find(|(variable, _)| *variable == name)
+        {
+            Some((_, known)) => {
+                let widest = known.widest(var_type);
+                let changed = *known != widest;
+                *known = widest;
+                changed
+            }
+            None => {
+                self.variables.push((name, var_type));
+                true
+            }
+        }
+    }
+}
+
+/// The types of the variables of a program, guessed from the values they are
+/// given, since the pseudocode does not declare them. A variable read from the
+/// input is text when it is only written back, an integer when it has to be
+/// one, such as an index, and a number that may be real otherwise.
+#[derive(Default, Debug)]
+pub struct Types<'a> {
+    pub main: Scope<'a>,
+    pub subprograms: HashMap<&'a str, Scope<'a>>,
+    /// What each function returns. Procedures are missing.
+    pub results: HashMap<&'a str, Type>,
+}
+
+impl<'a> Types<'a> {
+    pub fn infer(program: &[Statement<'a>]) -> Self {
+        let mut types = Types::default();
+        let subprograms = subprograms(program);
+        let definitions = definitions(program, &subprograms);
+        for subprogram in &definitions {
+            let scope = types.subprograms.entry(subprogram.name).or_default();
+            for &parameter in &subprogram.parameters {
+                scope.widen(parameter, Type::INTEGER);
+            }
+        }
+        let main_inputs = Inputs::of(program);
+        let inputs: HashMap<&str, Inputs> = definitions
+            .iter()
+            .map(|subprogram| (subprogram.name, Inputs::of(&subprogram.block)))
+            .collect();
+        // Types flow between variables and subprograms, so the program is
+        // walked until nothing new is learned. The subprograms are walked in
+        // the order they are defined, so the types do not depend on the
+        // order of a map.
+        loop {
+            let mut inference = Inference {
+                types: &mut types,
+                subprograms: &subprograms,
+                scope: None,
+                inputs: &main_inputs,
+                changed: false,
+            };
+            inference.block(program);
+            for subprogram in &definitions {
+                inference.scope = Some(subprogram.name);
+                inference.inputs = &inputs[subprogram.name];
+                inference.block(&subprogram.block);
+            }
+            if !inference.changed {
+                return types;
+            }
+        }
+    }
+
+    pub fn scope(&self, subprogram: Option<&str>) -> &Scope<'a> {
+        match subprogram {
+            Some(name) => &self.subprograms[name],
+            None => &self.main,
+        }
+    }
+
+    /// The type of the value of `expr`, for the variables of `scope`.
+    pub fn of(&self, scope: &Scope, expr: &Expression) -> Type {
+        let numeric = |expr1: &Expression, expr2: &Expression| {
+            let base = self.of(scope, expr1).base.max(self.of(scope, expr2).base);
+            Type::scalar(base.min(Base::Real))
+        };
+        match expr {
+            Expression::Constant(_) => Type::INTEGER,
+            Expression::RealConstant(_) => Type::scalar(Base::Real),
+            Expression::StringConstant(text) if unescape(text).chars().count() == 1 => {
+                Type::scalar(Base::Character)
+            }
+            Expression::StringConstant(_) => Type::scalar(Base::Text),
+            Expression::Variable(var) => scope.get(var).unwrap_or(Type::INTEGER),
+            Expression::Index(var, indices) => match scope.get(var) {
+                Some(var_type) if var_type.dimensions > 0 => Type {
+                    base: var_type.base,
+                    dimensions: var_type.dimensions.saturating_sub(indices.len()),
+                },
+                _ => Type::scalar(Base::Character),
+            },
+            Expression::Addition(expr1, expr2) => {
+                let (type1, type2) = (self.of(scope, expr1), self.of(scope, expr2));
+                if type1.is_textual() || type2.is_textual() {
+                    Type::scalar(Base::Text)
+                } else {
+                    numeric(expr1, expr2)
+                }
+            }
+            Expression::Subtraction(expr1, expr2)
+            | Expression::Multiplication(expr1, expr2)
+            | Expression::Division(expr1, expr2) => numeric(expr1, expr2),
+            Expression::Minus(expr) | Expression::Absolute(expr) => self.of(scope, expr),
+            Expression::Reminder(_, _)
+            | Expression::IntegerPart(_)
+            | Expression::Order(_, _, _)
+            | Expression::And(_, _)
+            | Expression::Or(_, _)
+            | Expression::Not(_) => Type::INTEGER,
+            Expression::Call(name, arguments) => {
+                if let Some(&result) = self.results.get(name) {
+                    return result;
+                }
+                match *name {
+                    "sqrt" | "radical" => Type::scalar(Base::Real),
+                    "abs" | "modul" | "max" | "min" => arguments
+                        .iter()
+                        .map(|argument| self.of(scope, argument))
+                        .fold(Type::INTEGER, Type::widest),
+                    _ => Type::INTEGER,
+                }
+            }
+        }
+    }
+}
+
+/// The subprograms of the program. When a name is defined twice, the last
+/// definition is the one that gets called.
+pub fn subprograms<'p, 'a>(program: &'p [Statement<'a>]) -> HashMap<&'a str, &'p Subprogram<'a>> {
+    let mut subprograms = HashMap::new();
+    for statement in program {
+        if let Instruction::Subprogram(subprogram) = &statement.instruction {
+            subprograms.insert(subprogram.name, subprogram);
+        }
+    }
+    subprograms
+}
+
+/// The subprograms that get called, in the order they are defined.
+pub fn definitions<'p, 'a>(
+    program: &'p [Statement<'a>],
+    subprograms: &HashMap<&'a str, &'p Subprogram<'a>>,
+) -> Vec<&'p Subprogram<'a>> {
+    program
+        .iter()
+        .filter_map(|statement| match &statement.instruction {
+            Instruction::Subprogram(subprogram)
+                if std::ptr::eq(subprograms[subprogram.name], subprogram) =>
+            {
+                Some(subprogram)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// How the variables of a scope that are read from the input are used. The
+/// input may hold numbers of any kind, or words, so the type of such a
+/// variable is guessed from what is done with it.
+#[derive(Default)]
+struct Inputs<'a> {
+    /// The variables read from the input.
+    read: HashSet<&'a str>,
+    /// The variables used in any way other than being written on their own.
+    used: HashSet<&'a str>,
+    /// The variables that must stay integers: indices, operands of `%` and
+    /// bounds of `pentru` loops.
+    integral: HashSet<&'a str>,
+}
+
+impl<'a> Inputs<'a> {
+    fn of(block: &[Statement<'a>]) -> Self {
+        let mut inputs = Inputs::default();
+        inputs.block(block);
+        inputs
+    }
+
+    /// Whether a variable only read and written back can hold a word.
+    fn is_echoed(&self, var: &str) -> bool {
+        self.read.contains(var) && !self.used.contains(var)
+    }
+
+    /// Whether a variable read from the input may hold a real number.
+    fn may_be_real(&self, var: &str) -> bool {
+        self.read.contains(var) && !self.integral.contains(var)
+    }
+
+    fn block(&mut self, block: &[Statement<'a>]) {
+        for statement in block {
+            match &statement.instruction {
+                Instruction::Read(targets) => {
+                    for target in targets {
+                        match target {
+                            LValue::Variable(var) => {
+                                self.read.insert(var);
+                            }
+                            LValue::Index(var, indices) => {
+                                self.read.insert(var);
+                                indices.iter().for_each(|index| {
+                                    self.expression(index);
+                                    self.integral(index);
+                                });
+                            }
+                        }
+                    }
+                }
+                Instruction::Write(writables, _) => {
+                    for writable in writables {
+                        match writable {
+                            Writable::Expression(expr)
+                                if !matches!(expr, Expression::Variable(_)) =>
+                            {
+                                self.expression(expr)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Instruction::Assignment(target, expr) => {
+                    // A variable also given values elsewhere keeps their type.
+                    match target {
+                        LValue::Variable(var) => {
+                            self.used.insert(var);
+                        }
+                        LValue::Index(var, indices) => {
+                            self.used.insert(var);
+                            indices.iter().for_each(|index| {
+                                self.expression(index);
+                                self.integral(index);
+                            });
+                        }
+                    }
+                    self.expression(expr);
+                }
+                Instruction::If(cond, if_block, else_block) => {
+                    self.expression(cond);
+                    self.block(if_block);
+                    if let Some(else_block) = else_block {
+                        self.block(else_block);
+                    }
+                }
+                Instruction::While(_, cond, block) => {
+                    self.expression(cond);
+                    self.block(block);
+                }
+                Instruction::For {
+                    variable,
+                    start_expr,
+                    end_expr,
+                    step,
+                    block,
+                } => {
+                    self.used.insert(variable);
+                    for expr in [start_expr, end_expr, step] {
+                        self.expression(expr);
+                        self.integral(expr);
+                    }
+                    self.block(block);
+                }
+                Instruction::Call(_, arguments) => arguments
+                    .iter()
+                    .for_each(|argument| self.expression(argument)),
+                Instruction::Return(expr) => expr.iter().for_each(|expr| self.expression(expr)),
+                Instruction::Subprogram(_) => {}
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression<'a>) {
+        let mut read = Vec::new();
+        variables(expr, &mut read);
+        self.used.extend(read);
+        self.integral_operands(expr);
+    }
+
+    /// Records the variables of an expression whose value must be an integer.
+    fn integral(&mut self, expr: &Expression<'a>) {
+        let mut read = Vec::new();
+        variables(expr, &mut read);
+        self.integral.extend(read);
+    }
+
+    fn integral_operands(&mut self, expr: &Expression<'a>) {
+        match expr {
+            Expression::Constant(_)
+            | Expression::RealConstant(_)
+            | Expression::StringConstant(_)
+            | Expression::Variable(_) => {}
+            Expression::Index(_, indices) => {
+                for index in indices {
+                    self.integral(index);
+                    self.integral_operands(index);
+                }
+            }
+            Expression::Reminder(expr1, expr2) => {
+                self.integral(expr1);
+                self.integral(expr2);
+            }
+            Expression::Addition(expr1, expr2)
+            | Expression::Subtraction(expr1, expr2)
+            | Expression::Multiplication(expr1, expr2)
+            | Expression::Division(expr1, expr2)
+            | Expression::Order(_, expr1, expr2)
+            | Expression::And(expr1, expr2)
+            | Expression::Or(expr1, expr2) => {
+                self.integral_operands(expr1);
+                self.integral_operands(expr2);
+            }
+            Expression::Minus(expr)
+            | Expression::Not(expr)
+            | Expression::IntegerPart(expr)
+            | Expression::Absolute(expr) => self.integral_operands(expr),
+            Expression::Call(_, arguments) => arguments
+                .iter()
+                .for_each(|argument| self.integral_operands(argument)),
+        }
+    }
+}
+
+struct Inference<'t, 'p, 'a> {
+    types: &'t mut Types<'a>,
+    subprograms: &'t HashMap<&'a str, &'p Subprogram<'a>>,
+    /// The subprogram being walked, or `None` for the main program.
+    scope: Option<&'a str>,
+    /// How the scope uses the variables it reads from the input.
+    inputs: &'t Inputs<'a>,
+    changed: bool,
+}
+
+impl<'t, 'p, 'a> Inference<'t, 'p, 'a> {
+    fn type_of(&self, expr: &Expression) -> Type {
+        self.types.of(self.types.scope(self.scope), expr)
+    }
+
+    fn widen(&mut self, name: &'a str, var_type: Type) {
+        let scope = match self.scope {
+            Some(subprogram) => self.types.subprograms.get_mut(subprogram).unwrap(),
+            None => &mut self.types.main,
+        };
+        self.changed |= scope.widen(name, var_type);
+    }
+
+    fn is_parameter(&self, var: &str) -> bool {
+        self.scope
+            .is_some_and(|name| self.subprograms[name].parameters.contains(&var))
+    }
+
+    /// Indexing a variable that holds a single value reads or changes one of
+    /// its characters. A parameter is given its value by the calls, which may
+    /// not have been walked yet, so indexing it only makes it an array,
+    /// unless it is already known to be text.
+    fn indexed(&mut self, var: &'a str, indices: usize, base: Base) {
+        match self.types.scope(self.scope).get(var) {
+            Some(var_type) if var_type.is_textual() => {}
+            Some(var_type) if var_type.dimensions == 0 && !self.is_parameter(var) => {
+                self.widen(var, Type::scalar(Base::Text))
+            }
+            _ => self.widen(
+                var,
+                Type {
+                    base,
+                    dimensions: indices,
+                },
+            ),
+        }
+    }
+
+    /// Variables that are only known as numbers become text when they are
+    /// used as text.
+    fn textual(&mut self, expr: &Expression<'a>) {
+        if let Expression::Variable(var) = expr {
+            match self.types.scope(self.scope).get(var) {
+                Some(var_type) if var_type.dimensions == 0 && var_type.base <= Base::Real => {
+                    self.widen(var, Type::scalar(Base::Text))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn block(&mut self, block: &[Statement<'a>]) {
+        for statement in block {
+            self.instruction(&statement.instruction);
+        }
+    }
+
+    fn instruction(&mut self, instruction: &Instruction<'a>) {
+        match instruction {
+            Instruction::Read(targets) => {
+                for target in targets {
+                    match target {
+                        LValue::Variable(var) if self.inputs.is_echoed(var) => {
+                            self.widen(var, Type::scalar(Base::Text))
+                        }
+                        LValue::Variable(var) | LValue::Index(var, _)
+                            if self.inputs.may_be_real(var) =>
+                        {
+                            self.store(target, Type::scalar(Base::Number))
+                        }
+                        _ => self.store(target, Type::INTEGER),
+                    }
+                }
+            }
+            Instruction::Write(writables, _) => {
+                for writable in writables {
+                    if let Writable::Expression(expr) = writable {
+                        self.expression(expr);
+                    }
+                }
+            }
+            Instruction::Assignment(target, expr) => {
+                self.expression(expr);
+                let value_type = self.type_of(expr);
+                self.store(target, value_type);
+            }
+            Instruction::If(cond, if_block, else_block) => {
+                self.expression(cond);
+                self.block(if_block);
+                if let Some(else_block) = else_block {
+                    self.block(else_block);
+                }
+            }
+            Instruction::While(_, cond, block) => {
+                self.expression(cond);
+                self.block(block);
+            }
+            Instruction::For {
+                variable,
+                start_expr,
+                end_expr,
+                step,
+                block,
+            } => {
+                for expr in [start_expr, end_expr, step] {
+                    self.expression(expr);
+                }
+                let start_type = self.type_of(start_expr);
+                let step_type = self.type_of(step);
+                self.widen(variable, start_type.widest(step_type));
+                self.block(block);
+            }
+            Instruction::Call(name, arguments) => self.call(name, arguments),
+            Instruction::Return(Some(expr)) => {
+                self.expression(expr);
+                if let Some(subprogram) = self.scope {
+                    let result_type = self.type_of(expr);
+                    let result = self.types.results.entry(subprogram).or_insert(result_type);
+                    let widest = result.widest(result_type);
+                    self.changed |= *result != widest;
+                    *result = widest;
+                }
+            }
+            Instruction::Return(None) | Instruction::Subprogram(_) => {}
+        }
+    }
+
+    fn store(&mut self, target: &LValue<'a>, value_type: Type) {
+        match target {
+            LValue::Variable(var) => self.widen(var, value_type),
+            LValue::Index(var, indices) => {
+                for index in indices {
+                    self.expression(index);
+                }
+                self.indexed(var, indices.len(), value_type.base);
+            }
+        }
+    }
+
+    fn call(&mut self, name: &'a str, arguments: &[Expression<'a>]) {
+        for argument in arguments {
+            self.expression(argument);
+        }
+        if name == "lungime" {
+            if let Some(argument) = arguments.first() {
+                self.textual(argument);
+            }
+        }
+        let subprogram = match self.subprograms.get(name) {
+            Some(subprogram) => subprogram,
+            None => return,
+        };
+        for (&parameter, argument) in subprogram.parameters.iter().zip(arguments) {
+            let argument_type = self.type_of(argument);
+            let scope = self.types.subprograms.get_mut(name).unwrap();
+            self.changed |= scope.widen(parameter, argument_type);
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression<'a>) {
+        match expr {
+            Expression::Constant(_)
+            | Expression::RealConstant(_)
+            | Expression::StringConstant(_)
+            | Expression::Variable(_) => {}
+            Expression::Index(var, indices) => {
+                for index in indices {
+                    self.expression(index);
+                }
+                if let Some(var_type) = self.types.scope(self.scope).get(var) {
+                    self.indexed(var, indices.len(), var_type.base);
+                }
+            }
+            Expression::Addition(expr1, expr2) | Expression::Order(_, expr1, expr2) => {
+                self.expression(expr1);
+                self.expression(expr2);
+                if self.type_of(expr1).is_textual() {
+                    self.textual(expr2);
+                }
+                if self.type_of(expr2).is_textual() {
+                    self.textual(expr1);
+                }
+            }
+            Expression::Subtraction(expr1, expr2)
+            | Expression::Multiplication(expr1, expr2)
+            | Expression::Division(expr1, expr2)
+            | Expression::Reminder(expr1, expr2)
+            | Expression::And(expr1, expr2)
+            | Expression::Or(expr1, expr2) => {
+                self.expression(expr1);
+                self.expression(expr2);
+            }
+            Expression::Minus(expr)
+            | Expression::Not(expr)
+            | Expression::IntegerPart(expr)
+            | Expression::Absolute(expr) => self.expression(expr),
+            Expression::Call(name, arguments) => self.call(name, arguments),
+        }
+    }
+}
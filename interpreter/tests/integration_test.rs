@@ -1,9 +1,17 @@
 use std::{
+    collections::hash_map::DefaultHasher,
     fs::read_to_string,
-    io::Cursor,
+    hash::{Hash, Hasher},
+    io::{Cursor, Write},
+    process::{Command, Stdio},
 };
 
-use interpreter::interpret;
+use interpreter::{
+    format_program, interpret, interpret_with_options, rewrite_loops, same_program, to_cpp,
+    to_pascal, to_python, CancellationToken, Diagnostic, Engine, ExecutionError,
+    ExecutionErrorKind, InterpreterError, Layout, Location, LoopKind, Options, RewriteError,
+};
+use proptest::{collection::vec, prelude::*, sample::select};
 use test_case::test_case;
 
 #[test_case("writes.pseudo", "4\n5\n13\n", ""; "write")]
@@ -16,6 +24,21 @@ use test_case::test_case;
 #[test_case("while.pseudo", "5\n4\n3\n2\n", ""; "while instruction")]
 #[test_case("for.pseudo", "0\n2\n4\n6\n8\n10\n12\n", ""; "for instruction")]
 #[test_case("fibonacci.pseudo", "Introduceti n:\nfib(10) = 55\n", "10"; "fibonacci")]
+#[test_case("reals.pseudo", "3.75\n3 3.5\n0.333333\n5 -4.75\n", "3\n4.5\n"; "real numbers")]
+#[test_case("math.pseudo", "7 -8 3\n7 2.5\n4 1.414214\n8 2.5 6\n", ""; "math built-ins")]
+#[test_case("logical.pseudo", "-2 in afara\n1 in interval\n4 in interval\n7 in interval\n10 in afara\nscurtcircuit\nok\n", ""; "logical operators")]
+#[test_case("comments.pseudo", "suma este 10\n", "1234"; "comments")]
+#[test_case("diacritics.pseudo", "1\n2\n24\n", "10"; "diacritics")]
+#[test_case("arrays.pseudo", "5\n1\n4\n1\n3\nsuma 14\n1 5 10 2\n", "5\n3\n1\n4\n1\n5\n"; "arrays")]
+#[test_case("matrix.pseudo", "1 2 3\n4 5 6\n7 8 9\n15 15\n1 2 3 6 9 8 7 4 5\n", "3\n"; "matrix")]
+#[test_case("subprograms.pseudo", "720\n8 6\n5 6\n", "6\n"; "subprograms")]
+#[test_case("strings.pseudo", "Salut, Ana\n5\nradar este palindrom\nvocale: 2\nordine lexicografica\nRadar\n", "Ana\nradar\n"; "strings")]
+#[test_case("write_inline.pseudo", "1 4 9 16 \tgata\nlinia 1\nlinia 2\nit's \"ok\"\n", "4\n"; "write without newline")]
+#[test_case("read_tokens.pseudo", "8\n24 Ion\n", "3 5\n\n  2\t3\n4 Ion\n"; "read tokens")]
+#[test_case("terminators.pseudo", "1\n3\n5\nsuma este 6\n3\n", "5"; "block terminators")]
+#[test_case("tabs_crlf.pseudo", "1\n2 par\n3\n4 par\n", "4"; "tabs and crlf")]
+#[test_case("array_arguments.pseudo", "1.75\n", ""; "array arguments")]
+#[test_case("read_reals.pseudo", "4.5 3\n3.75\n1.75\n", "4.5 3\n2\n1.5 2\n"; "read reals")]
 fn integration_test(file_name: &str, output: &str, input: &'static str) {
     let path = std::path::Path::new("tests")
         .join("resources")
@@ -28,3 +51,845 @@ fn integration_test(file_name: &str, output: &str, input: &'static str) {
     interpret(&mut reader, &mut writer, &program_string).unwrap();
     assert_eq!(std::str::from_utf8(&writer.into_inner()).unwrap(), output);
 }
+
+#[test_case("x <- 1\ndaca (x < 2) atunci\n  scrie 1\naltfel\n  scrie 2", "1\n"; "daca")]
+#[test_case("x <- 0\ncat timp (x + 1) * 2 < 8 executa\n  x <- x + 1\nscrie x", "3\n"; "cat timp")]
+fn parenthesized_conditions(program: &str, output: &str) {
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+
+    interpret(&mut reader, &mut writer, program).unwrap();
+    assert_eq!(std::str::from_utf8(&writer.into_inner()).unwrap(), output);
+}
+
+#[test]
+fn negative_square_root() {
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+
+    let result = interpret(&mut reader, &mut writer, "x <- 2\nscrie sqrt(x - 10)");
+    assert!(matches!(
+        result,
+        Err(InterpreterError::ExecutionError(ExecutionError {
+            kind: ExecutionErrorKind::NegativeSquareRootError,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn error_location() {
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+
+    let program = "x <- 3\ncat timp x >= 0 executa\n  scrie 6 / x\n  x <- x - 1";
+    match interpret(&mut reader, &mut writer, program) {
+        Err(InterpreterError::ExecutionError(error)) => {
+            assert_eq!(error.kind, ExecutionErrorKind::ZeroDivisionError);
+            assert_eq!(
+                error.location,
+                Some(Location {
+                    line: 3,
+                    source: "scrie 6 / x".to_string()
+                })
+            );
+            assert_eq!(
+                error.to_string(),
+                "Linia 3: impartire la zero\n  scrie 6 / x"
+            );
+        }
+        _ => panic!("Expected a division by zero"),
+    }
+    assert_eq!(
+        std::str::from_utf8(&writer.into_inner()).unwrap(),
+        "2\n3\n6\n"
+    );
+}
+
+#[test_case("v[1] <- 1\nscrie v[2]", ExecutionErrorKind::UninitializedElementError("v[2]".to_string()); "uninitialized element")]
+#[test_case("v[1] <- 1\nscrie v[0 - 1]", ExecutionErrorKind::IndexOutOfBoundsError("v".to_string(), -1); "negative index")]
+#[test_case("v[1.5] <- 1", ExecutionErrorKind::IndexNotIntegerError("v".to_string()); "real index")]
+#[test_case("x <- 1\nx[1] <- 2", ExecutionErrorKind::NotAnArrayError("x".to_string()); "not an array")]
+#[test_case("a[1][1] <- 1\nscrie a[1][2]", ExecutionErrorKind::UninitializedElementError("a[1][2]".to_string()); "uninitialized matrix element")]
+#[test_case("a[1] <- 1\na[1][2] <- 2", ExecutionErrorKind::NotAnArrayError("a[1]".to_string()); "matrix line not an array")]
+#[test_case("a[1, 1] <- 1\nscrie a[1, 2.5]", ExecutionErrorKind::IndexNotIntegerError("a".to_string()); "real matrix index")]
+#[test_case("v[1] <- 1\nscrie v + 1", ExecutionErrorKind::TypeError("un numar", "un tablou"); "array as number")]
+#[test_case("s <- 'abc'\nscrie s[3]", ExecutionErrorKind::IndexOutOfBoundsError("s".to_string(), 3); "string index")]
+#[test_case("s <- 'abc'\ns[0] <- 'xy'", ExecutionErrorKind::TypeError("un caracter", "un text"); "string element")]
+#[test_case("s <- 'abc'\nscrie s + 1", ExecutionErrorKind::TypeError("un text", "un numar intreg"); "string concatenation")]
+#[test_case("s <- 'abc'\nscrie s * 2", ExecutionErrorKind::TypeError("un numar", "un text"); "string arithmetic")]
+fn array_errors(program: &str, expected: ExecutionErrorKind) {
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+
+    match interpret(&mut reader, &mut writer, program) {
+        Err(InterpreterError::ExecutionError(error)) => assert_eq!(error.kind, expected),
+        _ => panic!("Expected an execution error"),
+    }
+}
+
+#[test_case("functie f(n)\n  returneaza f(n + 1)\nscrie f(1)", ExecutionErrorKind::CallDepthError(1000); "infinite recursion")]
+#[test_case("subprogram p()\n  scrie 1\nscrie p()", ExecutionErrorKind::NoReturnValueError("p".to_string()); "procedure in expression")]
+#[test_case("functie f(a)\n  returneaza a\nscrie f(1, 2)", ExecutionErrorKind::ArgumentCountError("f".to_string(), 1, 2); "argument count")]
+#[test_case("x <- 1\nsubprogram p()\n  scrie x\np()", ExecutionErrorKind::VariableNotDefinedError("x".to_string()); "local scope")]
+fn subprogram_errors(program: &str, expected: ExecutionErrorKind) {
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+
+    match interpret(&mut reader, &mut writer, program) {
+        Err(InterpreterError::ExecutionError(error)) => assert_eq!(error.kind, expected),
+        _ => panic!("Expected an execution error"),
+    }
+}
+
+#[test_case(Engine::Bytecode; "bytecode")]
+#[test_case(Engine::TreeWalker; "tree walker")]
+fn call_depth_limit(engine: Engine) {
+    let program = "functie f(n)\n  returneaza f(n + 1)\nscrie f(1)";
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+    let options = Options {
+        engine,
+        ..Options::default()
+    };
+
+    match interpret_with_options(&mut reader, &mut writer, program, &options) {
+        Err(InterpreterError::ExecutionError(error)) => {
+            assert_eq!(error.kind, ExecutionErrorKind::CallDepthError(1000))
+        }
+        _ => panic!("Expected the recursion to be stopped"),
+    }
+}
+
+#[test_case("x <- 2147483647\ny <- x + 1", 2; "addition")]
+#[test_case("x <- 0 - 2147483647\nx <- x - 1\nscrie x\nx <- x - 1", 4; "subtraction")]
+#[test_case("x <- 65536\nscrie x * x", 2; "multiplication")]
+#[test_case("x <- 0 - 2147483647 - 1\nscrie x / (0 - 1)", 2; "division")]
+#[test_case("x <- 0 - 2147483647 - 1\nscrie -x", 2; "negation")]
+#[test_case("x <- 0 - 2147483647 - 1\nscrie abs(x)", 2; "absolute value")]
+#[test_case("scrie [3000000000.5]", 1; "integer part")]
+#[test_case("p <- 1\npentru i <- 1, 40 executa\n  p <- p * 2", 3; "for loop")]
+fn overflow(program: &str, line: usize) {
+    for engine in [Engine::Bytecode, Engine::TreeWalker] {
+        let mut reader = Cursor::new("");
+        let mut writer = Cursor::new(Vec::new());
+        let options = Options {
+            engine,
+            ..Options::default()
+        };
+
+        match interpret_with_options(&mut reader, &mut writer, program, &options) {
+            Err(InterpreterError::ExecutionError(error)) => {
+                assert_eq!(error.kind, ExecutionErrorKind::OverflowError);
+                assert_eq!(error.location.unwrap().line, line);
+            }
+            _ => panic!("Expected an overflow"),
+        }
+    }
+}
+
+#[test_case("citeste a, b", "1", ExecutionErrorKind::EndOfInputError; "end of input")]
+#[test_case("citeste a, b", "1 2x", ExecutionErrorKind::NumberParsingError("2x".to_string()); "malformed number")]
+#[test_case("citeste a", "3000000000", ExecutionErrorKind::OverflowError; "integer too large")]
+#[test_case("citeste a", "-2147483649", ExecutionErrorKind::OverflowError; "integer too small")]
+fn read_errors(program: &str, input: &str, expected: ExecutionErrorKind) {
+    let mut reader = Cursor::new(input);
+    let mut writer = Cursor::new(Vec::new());
+
+    match interpret(&mut reader, &mut writer, program) {
+        Err(InterpreterError::ExecutionError(error)) => assert_eq!(error.kind, expected),
+        _ => panic!("Expected an execution error"),
+    }
+}
+
+#[test]
+fn forced_layout() {
+    let program = "daca 1 atunci\n  scrie 1\nsf daca";
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+    let options = Options {
+        layout: Some(Layout::Indentation),
+        ..Options::default()
+    };
+
+    match interpret_with_options(&mut reader, &mut writer, program, &options) {
+        Err(InterpreterError::ParsingError(diagnostic)) => assert_eq!(diagnostic.line, 3),
+        _ => panic!("Expected a syntax error"),
+    }
+    interpret(&mut reader, &mut writer, program).unwrap();
+    assert_eq!(std::str::from_utf8(&writer.into_inner()).unwrap(), "1\n");
+}
+
+fn run_with_engine(engine: Engine, program: &str, input: &str) -> (String, Option<String>) {
+    let mut reader = Cursor::new(input);
+    let mut writer = Cursor::new(Vec::new());
+    let options = Options {
+        engine,
+        ..Options::default()
+    };
+
+    let result = interpret_with_options(&mut reader, &mut writer, program, &options);
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    (output, result.err().map(|e| e.to_string()))
+}
+
+#[test_case("fibonacci.pseudo", "10"; "fibonacci")]
+#[test_case("for.pseudo", ""; "for instruction")]
+#[test_case("reals.pseudo", "3\n4.5\n"; "real numbers")]
+#[test_case("logical.pseudo", ""; "logical operators")]
+#[test_case("arrays.pseudo", "5\n3\n1\n4\n1\n5\n"; "arrays")]
+#[test_case("matrix.pseudo", "3\n"; "matrix")]
+#[test_case("subprograms.pseudo", "6\n"; "subprograms")]
+#[test_case("strings.pseudo", "Ana\nradar\n"; "strings")]
+#[test_case("read_tokens.pseudo", "3 5\n\n  2\t3\n4 Ion\n"; "read tokens")]
+#[test_case("terminators.pseudo", "5"; "block terminators")]
+fn engines_agree_on_programs(file_name: &str, input: &str) {
+    let path = std::path::Path::new("tests")
+        .join("resources")
+        .join(file_name);
+    let program = read_to_string(path).expect("Could not read file");
+
+    assert_eq!(
+        run_with_engine(Engine::Bytecode, &program, input),
+        run_with_engine(Engine::TreeWalker, &program, input)
+    );
+    #[cfg(feature = "by-name")]
+    {
+        let mut reader = Cursor::new(input);
+        let mut writer = Cursor::new(Vec::new());
+        let result = interpreter::interpret_by_name(&mut reader, &mut writer, &program);
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            (output, result.err().map(|e| e.to_string())),
+            run_with_engine(Engine::TreeWalker, &program, input)
+        );
+    }
+}
+
+#[test_case("pentru i <- 10, 1, 0 - 3 executa\n  scrie i"; "descending step")]
+#[test_case("p <- 1\npentru i <- 1, 10, p executa\n  scrie i\n  p <- 2"; "variable step")]
+#[test_case("pentru i <- 1, 3 executa\n  scrie i\n  i <- i + 0.5"; "real loop variable")]
+#[test_case("functie f(n)\n  pentru i <- 1, n executa\n    daca i * i > n atunci\n      returneaza i\nscrie f(10)\nscrie f(0)"; "return from loop")]
+#[test_case("x <- 0\nscrie x > 0 si 1 / x > 0, 1 sau 1 / x\nscrie 2 si 3"; "short circuit")]
+#[test_case("x <- 3\ncat timp x >= 0 executa\n  scrie 6 / x\n  x <- x - 1"; "error location")]
+#[test_case("subprogram p(n)\n  scrie n\n  daca n > 0 atunci\n    p(n - 1)\np(3)\nscrie max(1, 2.5)\nreturneaza\nscrie 0"; "procedures")]
+#[test_case("subprogram p()\n  scrie 1\nscrie p()"; "procedure in expression")]
+#[test_case("functie f(a)\n  scrie a[0]\nv[1] <- 2\nscrie f(v)"; "error in subprogram")]
+fn engines_agree(program: &str) {
+    assert_eq!(
+        run_with_engine(Engine::Bytecode, program, ""),
+        run_with_engine(Engine::TreeWalker, program, "")
+    );
+}
+
+#[test_case(Engine::Bytecode; "bytecode")]
+#[test_case(Engine::TreeWalker; "tree walker")]
+fn step_limit(engine: Engine) {
+    let program = "scrie 1\ni <- 0\ncat timp 1 executa\n  i <- i + 1\n  scrie i";
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+    let options = Options {
+        engine,
+        max_steps: Some(9),
+        ..Options::default()
+    };
+
+    match interpret_with_options(&mut reader, &mut writer, program, &options) {
+        Err(InterpreterError::ExecutionError(error)) => {
+            assert_eq!(error.kind, ExecutionErrorKind::StepLimitExceeded(9));
+            assert_eq!(error.location.unwrap().line, 3);
+        }
+        _ => panic!("Expected the program to be stopped"),
+    }
+    assert_eq!(
+        std::str::from_utf8(&writer.into_inner()).unwrap(),
+        "1\n1\n2\n"
+    );
+}
+
+#[test_case(Engine::Bytecode; "bytecode")]
+#[test_case(Engine::TreeWalker; "tree walker")]
+fn time_limit(engine: Engine) {
+    let mut reader = Cursor::new("");
+    let mut writer = Cursor::new(Vec::new());
+    let options = Options {
+        engine,
+        time_limit: Some(std::time::Duration::from_millis(50)),
+        ..Options::default()
+    };
+
+    let result = interpret_with_options(
+        &mut reader,
+        &mut writer,
+        "repeta\n  x <- 1\npana cand 0",
+        &options,
+    );
+    assert!(matches!(
+        result,
+        Err(InterpreterError::ExecutionError(ExecutionError {
+            kind: ExecutionErrorKind::TimeLimitExceeded(_),
+            ..
+        }))
+    ));
+}
+
+#[test_case(Engine::Bytecode; "bytecode")]
+#[test_case(Engine::TreeWalker; "tree walker")]
+fn cancellation(engine: Engine) {
+    let cancellation = CancellationToken::new();
+    let options = Options {
+        engine,
+        cancellation: Some(cancellation.clone()),
+        ..Options::default()
+    };
+    let program = std::thread::spawn(move || {
+        let mut reader = Cursor::new("");
+        let mut writer = Cursor::new(Vec::new());
+        let program = "scrie 'start'\ncat timp 1 executa\n  x <- 1";
+        let result = interpret_with_options(&mut reader, &mut writer, program, &options);
+        (result, writer.into_inner())
+    });
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    cancellation.cancel();
+
+    let (result, output) = program.join().unwrap();
+    assert!(matches!(
+        result,
+        Err(InterpreterError::ExecutionError(ExecutionError {
+            kind: ExecutionErrorKind::Cancelled,
+            ..
+        }))
+    ));
+    assert_eq!(std::str::from_utf8(&output).unwrap(), "start\n");
+}
+
+/// Writes a program in another language, like `to_cpp`.
+type Translator = fn(&str, &Options) -> Result<String, Diagnostic>;
+
+#[test_case("writes"; "write")]
+#[test_case("reads"; "read")]
+#[test_case("if1"; "simple if")]
+#[test_case("if2"; "nested if")]
+#[test_case("if3"; "else if")]
+#[test_case("else_if"; "else if chain")]
+#[test_case("do_while"; "do while")]
+#[test_case("repeat"; "repeat")]
+#[test_case("while"; "while instruction")]
+#[test_case("for"; "for instruction")]
+#[test_case("fibonacci"; "fibonacci")]
+#[test_case("reals"; "real numbers")]
+#[test_case("math"; "math built-ins")]
+#[test_case("logical"; "logical operators")]
+#[test_case("comments"; "comments")]
+#[test_case("diacritics"; "diacritics")]
+#[test_case("arrays"; "arrays")]
+#[test_case("matrix"; "matrix")]
+#[test_case("subprograms"; "subprograms")]
+#[test_case("strings"; "strings")]
+#[test_case("write_inline"; "write without newline")]
+#[test_case("read_tokens"; "read tokens")]
+#[test_case("terminators"; "block terminators")]
+#[test_case("tabs_crlf"; "tabs and crlf")]
+#[test_case("array_arguments"; "array arguments")]
+#[test_case("read_reals"; "read reals")]
+fn translations(name: &str) {
+    let resources = std::path::Path::new("tests").join("resources");
+    let program =
+        read_to_string(resources.join(format!("{}.pseudo", name))).expect("Could not read file");
+    let options = Options::default();
+    let translators: [(&str, &str, Translator); 3] = [
+        ("cpp", "cpp", to_cpp),
+        ("pascal", "pas", to_pascal),
+        ("python", "py", to_python),
+    ];
+
+    for (directory, extension, translate) in translators {
+        let golden = resources
+            .join(directory)
+            .join(format!("{}.{}", name, extension));
+        let expected = read_to_string(golden).expect("Could not read file");
+        assert_eq!(translate(&program, &options).unwrap(), expected);
+    }
+}
+
+#[test_case("writes", ""; "write")]
+#[test_case("reads", "1\n2\n3\n4\n5\n"; "read")]
+#[test_case("else_if", ""; "else if chain")]
+#[test_case("repeat", ""; "repeat")]
+#[test_case("for", ""; "for instruction")]
+#[test_case("fibonacci", "10"; "fibonacci")]
+#[test_case("reals", "3\n4.5\n"; "real numbers")]
+#[test_case("math", ""; "math built-ins")]
+#[test_case("logical", ""; "logical operators")]
+#[test_case("arrays", "5\n3\n1\n4\n1\n5\n"; "arrays")]
+#[test_case("matrix", "3\n"; "matrix")]
+#[test_case("subprograms", "6\n"; "subprograms")]
+#[test_case("strings", "Ana\nradar\n"; "strings")]
+#[test_case("write_inline", "4\n"; "write without newline")]
+#[test_case("read_tokens", "3 5\n\n  2\t3\n4 Ion\n"; "read tokens")]
+#[test_case("array_arguments", ""; "array arguments")]
+#[test_case("read_reals", "4.5 3\n2\n1.5 2\n"; "read reals")]
+#[test_case("read_reals", "7 2\n2\n1 2\n"; "read integers")]
+#[test_case("functie f(v)\n  v[1] <- 0\n  returneaza v[1]\nv[1] <- 5\nscrie f(v), ' ', v[1]", ""; "array parameter")]
+#[test_case("citeste x, y\nscrie x * y + 0.5, ' ', x / 4.0", "3\n0.25\n"; "real input")]
+fn compiled_cpp(program: &str, input: &str) {
+    // Without a C++ compiler there is nothing to run.
+    if Command::new("g++").arg("--version").output().is_err() {
+        return;
+    }
+    let program = program_source(program);
+    let directory = work_directory("cpp", &program);
+    let source = directory.join("program.cpp");
+    let executable = directory.join("program");
+    std::fs::write(&source, to_cpp(&program, &Options::default()).unwrap()).unwrap();
+
+    let status = Command::new("g++")
+        .arg("-o")
+        .arg(&executable)
+        .arg(&source)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = output_of(&mut Command::new(&executable), input);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let (expected, error) = run_with_engine(Engine::Bytecode, &program, input);
+    assert_eq!(error, None);
+    assert_eq!(output, expected);
+}
+
+#[test_case("writes", ""; "write")]
+#[test_case("reads", "1\n2\n3\n4\n5\n"; "read")]
+#[test_case("for", ""; "for instruction")]
+#[test_case("reals", "3\n4.5\n"; "real numbers")]
+#[test_case("logical", ""; "logical operators")]
+#[test_case("arrays", "5\n3\n1\n4\n1\n5\n"; "arrays")]
+#[test_case("matrix", "3\n"; "matrix")]
+#[test_case("subprograms", "6\n"; "subprograms")]
+#[test_case("strings", "Ana\nradar\n"; "strings")]
+#[test_case("read_tokens", "3 5\n\n  2\t3\n4 Ion\n"; "read tokens")]
+#[test_case("functie f(v)\n  v[1] <- 0\n  returneaza v[1]\nv[1] <- 5\nscrie f(v), ' ', v[1]", ""; "array parameter")]
+#[test_case("read_reals", "4.5 3\n2\n1.5 2\n"; "read reals")]
+#[test_case("pentru i <- 1, 3 executa\n  scrie i\nscrie i", ""; "loop variable after the loop")]
+#[test_case("i <- 7\npentru i <- 1, 0 executa\n  scrie i\nscrie i", ""; "loop that does not run")]
+fn python_programs(program: &str, input: &str) {
+    // Without Python there is nothing to run.
+    if Command::new("python3").arg("--version").output().is_err() {
+        return;
+    }
+    let program = program_source(program);
+    let directory = work_directory("python", &program);
+    let source = directory.join("program.py");
+    std::fs::write(&source, to_python(&program, &Options::default()).unwrap()).unwrap();
+
+    let output = output_of(Command::new("python3").arg(&source), input);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let (expected, error) = run_with_engine(Engine::Bytecode, &program, input);
+    assert_eq!(error, None);
+    assert_eq!(output, expected);
+}
+
+/// The program in the resource file `name`, or `name` itself when there is
+/// no such file.
+fn program_source(name: &str) -> String {
+    let path = std::path::Path::new("tests")
+        .join("resources")
+        .join(format!("{}.pseudo", name));
+    read_to_string(path).unwrap_or_else(|_| name.to_string())
+}
+
+/// A new directory for the files a translation of `program` needs to run.
+fn work_directory(language: &str, program: &str) -> std::path::PathBuf {
+    let mut hasher = DefaultHasher::new();
+    program.hash(&mut hasher);
+    let directory = std::env::temp_dir().join(format!(
+        "interpreter-{}-{}-{:x}",
+        language,
+        std::process::id(),
+        hasher.finish()
+    ));
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn output_of(command: &mut Command, input: &str) -> String {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+}
+
+#[test_case("pentru i <- 1, 5 executa\n  scrie i", "for i := 1 to 5 do begin"; "step one")]
+#[test_case("pentru i <- 5, 1, -1 executa\n  scrie i", "for i := 5 downto 1 do begin"; "step minus one")]
+#[test_case("pentru i <- 1, 9, 2 executa\n  scrie i", "while i <= 9 do begin"; "larger step")]
+#[test_case("pentru i <- 9, 1, -2 executa\n  scrie i", "while i >= 1 do begin"; "larger negative step")]
+#[test_case("p <- 1\npentru i <- 1, 9, p executa\n  scrie i", "while ((p >= 0) and (i <= 9)) or ((p < 0) and (i >= 9)) do begin"; "variable step")]
+#[test_case("n <- 9\npentru i <- 1, n executa\n  n <- n - 1", "while i <= n do begin"; "changing end")]
+#[test_case("pentru i <- 1, 3 executa\n  scrie i\nscrie i", "while i <= 3 do begin"; "variable read after the loop")]
+#[test_case("x <- 1.5\nscrie x", "writeln(text(x));"; "real")]
+#[test_case("citeste n, nume\nscrie nume", "citeste(nume);"; "word")]
+#[test_case("array_arguments", "function suma(v: VectorReal; n: longint): real;"; "array argument passed on")]
+#[test_case("array_arguments", "function medie(v: VectorReal; n: longint): real;"; "array argument")]
+fn pascal_lines(program: &str, expected: &str) {
+    let pascal = to_pascal(&program_source(program), &Options::default()).unwrap();
+    assert!(
+        pascal.lines().any(|line| line.trim() == expected),
+        "{}",
+        pascal
+    );
+}
+
+/// Every rewrite `rewrite_loops` can do.
+const LOOP_REWRITES: [(LoopKind, LoopKind); 9] = [
+    (LoopKind::For, LoopKind::While),
+    (LoopKind::For, LoopKind::DoWhile),
+    (LoopKind::For, LoopKind::Repeat),
+    (LoopKind::While, LoopKind::DoWhile),
+    (LoopKind::While, LoopKind::Repeat),
+    (LoopKind::DoWhile, LoopKind::While),
+    (LoopKind::DoWhile, LoopKind::Repeat),
+    (LoopKind::Repeat, LoopKind::While),
+    (LoopKind::Repeat, LoopKind::DoWhile),
+];
+
+/// Runs a program that must parse, giving what it wrote and how it failed.
+fn run_steps(
+    program: &str,
+    input: &str,
+    max_steps: Option<u64>,
+) -> (String, Option<ExecutionErrorKind>) {
+    let mut reader = Cursor::new(input);
+    let mut writer = Cursor::new(Vec::new());
+    let options = Options {
+        max_steps,
+        ..Options::default()
+    };
+
+    let result = interpret_with_options(&mut reader, &mut writer, program, &options);
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    match result {
+        Ok(()) => (output, None),
+        Err(InterpreterError::ExecutionError(e)) => (output, Some(e.kind)),
+        Err(InterpreterError::ParsingError(e)) => panic!("{}\n{}", e, program),
+    }
+}
+
+#[test_case("writes.pseudo", ""; "write")]
+#[test_case("reads.pseudo", "1\n2\n3\n4\n5\n"; "read")]
+#[test_case("if3.pseudo", ""; "else if")]
+#[test_case("do_while.pseudo", ""; "do while")]
+#[test_case("repeat.pseudo", ""; "repeat")]
+#[test_case("while.pseudo", ""; "while instruction")]
+#[test_case("for.pseudo", ""; "for instruction")]
+#[test_case("fibonacci.pseudo", "10"; "fibonacci")]
+#[test_case("reals.pseudo", "3\n4.5\n"; "real numbers")]
+#[test_case("logical.pseudo", ""; "logical operators")]
+#[test_case("comments.pseudo", "1234"; "comments")]
+#[test_case("diacritics.pseudo", "10"; "diacritics")]
+#[test_case("arrays.pseudo", "5\n3\n1\n4\n1\n5\n"; "arrays")]
+#[test_case("matrix.pseudo", "3\n"; "matrix")]
+#[test_case("subprograms.pseudo", "6\n"; "subprograms")]
+#[test_case("strings.pseudo", "Ana\nradar\n"; "strings")]
+#[test_case("write_inline.pseudo", "4\n"; "write without newline")]
+#[test_case("read_tokens.pseudo", "3 5\n\n  2\t3\n4 Ion\n"; "read tokens")]
+#[test_case("terminators.pseudo", "5"; "block terminators")]
+#[test_case("tabs_crlf.pseudo", "4"; "tabs and crlf")]
+fn loop_rewrites_on_programs(file_name: &str, input: &str) {
+    let path = std::path::Path::new("tests")
+        .join("resources")
+        .join(file_name);
+    let program = read_to_string(path).expect("Could not read file");
+    let expected = run_steps(&program, input, None);
+
+    for (from, to) in LOOP_REWRITES {
+        let rewritten = rewrite_loops(&program, from, to, &Options::default()).unwrap();
+        assert_eq!(
+            run_steps(&rewritten, input, None),
+            expected,
+            "{:?} -> {:?}\n{}",
+            from,
+            to,
+            rewritten
+        );
+    }
+}
+
+#[test_case("pentru i <- 1, 3 executa\n  scrie i", LoopKind::For, LoopKind::While, "i <- 1\ncat timp i <= 3 executa\n  scrie i\n  i <- i + 1\n"; "for as while")]
+#[test_case("pentru i <- 9, 1, -2 executa\n  scrie i", LoopKind::For, LoopKind::Repeat, "i <- 9\ndaca i >= 1 atunci\n  repeta\n    scrie i\n    i <- i - 2\n  pana cand i < 1\n"; "descending for as repeat")]
+#[test_case("p <- 1\npentru i <- 1, 9, p executa\n  p <- 2", LoopKind::For, LoopKind::While, "p <- 1\ni <- 1\npas <- p\ncat timp pas >= 0 si i <= 9 sau pas < 0 si i >= 9 executa\n  p <- 2\n  i <- i + pas\n"; "changing step")]
+#[test_case("pas <- 3\npentru i <- 1, 9, pas executa\n  pentru j <- 1, i, i - 2 executa\n    scrie j", LoopKind::For, LoopKind::While, "pas <- 3\ni <- 1\ncat timp pas >= 0 si i <= 9 sau pas < 0 si i >= 9 executa\n  j <- 1\n  pas2 <- i - 2\n  cat timp pas2 >= 0 si j <= i sau pas2 < 0 si j >= i executa\n    scrie j\n    j <- j + pas2\n  i <- i + pas\n"; "nested steps")]
+#[test_case("cat timp x < 3 si y <> 0 executa\n  x <- x + 1", LoopKind::While, LoopKind::Repeat, "daca x < 3 si y <> 0 atunci\n  repeta\n    x <- x + 1\n  pana cand x >= 3 sau y = 0\n"; "while as repeat")]
+#[test_case("executa\n  x <- x + 1\ncat timp not x", LoopKind::DoWhile, LoopKind::While, "x <- x + 1\ncat timp not x executa\n  x <- x + 1\n"; "do while as while")]
+#[test_case("repeta\n  x <- x + 1\npana cand x", LoopKind::Repeat, LoopKind::DoWhile, "executa\n  x <- x + 1\ncat timp not x\n"; "repeat as do while")]
+fn loop_rewrites(program: &str, from: LoopKind, to: LoopKind, expected: &str) {
+    assert_eq!(
+        rewrite_loops(program, from, to, &Options::default()).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn loop_rewrites_into_for() {
+    assert!(matches!(
+        rewrite_loops(
+            "cat timp 0 executa\n  x <- 1",
+            LoopKind::While,
+            LoopKind::For,
+            &Options::default()
+        ),
+        Err(RewriteError::ForTarget)
+    ));
+}
+
+#[test]
+fn loop_rewrites_keep_many_steps() {
+    // Ten loops nested, each keeping its step, with the names the first
+    // steps would take already in use.
+    let mut program = String::from("pas <- 1\npas3 <- 1\n");
+    for depth in 0..10 {
+        let indentation = "  ".repeat(depth);
+        program += &format!(
+            "{}pentru v{} <- 1, 2, pas + 0 executa\n",
+            indentation, depth
+        );
+    }
+    program += &format!("{}scrie v0 + v9, ' ', pas3", "  ".repeat(10));
+    let expected = run_steps(&program, "", None);
+
+    let rewritten = rewrite_loops(
+        &program,
+        LoopKind::For,
+        LoopKind::While,
+        &Options::default(),
+    )
+    .unwrap();
+    assert_eq!(run_steps(&rewritten, "", None), expected, "{}", rewritten);
+    assert!(rewritten.contains("pas12 <- "), "{}", rewritten);
+}
+
+/// What the resource programs that read are given.
+const RESOURCE_INPUTS: [(&str, &str); 14] = [
+    ("reads.pseudo", "1\n2\n3\n4\n5\n"),
+    ("fibonacci.pseudo", "10"),
+    ("reals.pseudo", "3\n4.5\n"),
+    ("comments.pseudo", "1234"),
+    ("diacritics.pseudo", "10"),
+    ("arrays.pseudo", "5\n3\n1\n4\n1\n5\n"),
+    ("matrix.pseudo", "3\n"),
+    ("subprograms.pseudo", "6\n"),
+    ("strings.pseudo", "Ana\nradar\n"),
+    ("write_inline.pseudo", "4\n"),
+    ("read_tokens.pseudo", "3 5\n\n  2\t3\n4 Ion\n"),
+    ("terminators.pseudo", "5"),
+    ("tabs_crlf.pseudo", "4"),
+    ("read_reals.pseudo", "4.5 3\n2\n1.5 2\n"),
+];
+
+#[test]
+fn formatting_programs() {
+    let resources = std::path::Path::new("tests").join("resources");
+    for entry in std::fs::read_dir(resources).unwrap() {
+        let path = entry.unwrap().path();
+        if path
+            .extension()
+            .is_none_or(|extension| extension != "pseudo")
+        {
+            continue;
+        }
+        let program = read_to_string(&path).expect("Could not read file");
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        let input = RESOURCE_INPUTS
+            .iter()
+            .find(|(name, _)| *name == file_name)
+            .map_or("", |&(_, input)| input);
+
+        let options = Options::default();
+        let formatted = format_program(&program, &options).unwrap();
+        assert!(
+            same_program(&formatted, &program, &options).unwrap(),
+            "{}",
+            formatted
+        );
+        assert_eq!(format_program(&formatted, &options).unwrap(), formatted);
+        assert_eq!(
+            run_steps(&formatted, input, None),
+            run_steps(&program, input, None),
+            "{}",
+            formatted
+        );
+    }
+}
+
+/// The outline of a generated program, whose loops all count to a bound.
+#[derive(Clone, Debug)]
+enum Shape {
+    Write(&'static str),
+    Assign(&'static str, &'static str),
+    If(&'static str, Vec<Shape>),
+    Loop {
+        kind: LoopKind,
+        start: &'static str,
+        end: &'static str,
+        step: &'static str,
+        body: Vec<Shape>,
+    },
+}
+
+const EXPRESSIONS: [&str; 8] = ["a", "b", "a + b", "a * 2 - b", "b % 3", "a / 2", "i1", "-a"];
+const CONDITIONS: [&str; 5] = [
+    "a < b",
+    "a % 2 = 0",
+    "not a > 3",
+    "b <> 3 sau a = 1",
+    "a >= 0 si b < 5",
+];
+const BOUNDS: [&str; 7] = ["0", "1", "4", "-2", "a", "b", "a + b"];
+const STEPS: [&str; 7] = ["1", "2", "-1", "-2", "0.5", "b", "a - b"];
+const LOOP_KINDS: [LoopKind; 4] = [
+    LoopKind::For,
+    LoopKind::While,
+    LoopKind::DoWhile,
+    LoopKind::Repeat,
+];
+
+fn shapes() -> impl Strategy<Value = Vec<Shape>> {
+    let leaf = prop_oneof![
+        select(&EXPRESSIONS[..]).prop_map(Shape::Write),
+        (select(&["a", "b"][..]), select(&EXPRESSIONS[..]))
+            .prop_map(|(var, expr)| Shape::Assign(var, expr)),
+    ];
+    let shape = leaf.prop_recursive(3, 32, 4, |shape| {
+        let block = vec(shape, 1..4);
+        prop_oneof![
+            (select(&CONDITIONS[..]), block.clone()).prop_map(|(cond, body)| Shape::If(cond, body)),
+            (
+                select(&LOOP_KINDS[..]),
+                select(&BOUNDS[..]),
+                select(&BOUNDS[..]),
+                select(&STEPS[..]),
+                block
+            )
+                .prop_map(|(kind, start, end, step, body)| Shape::Loop {
+                    kind,
+                    start,
+                    end,
+                    step,
+                    body
+                }),
+        ]
+    });
+    vec(shape, 1..6)
+}
+
+/// Writes the program outlined by `shapes`, naming the loop variables after
+/// how deeply the loops are nested.
+fn render(shapes: &[Shape], depth: usize, program: &mut String) {
+    let line = |program: &mut String, depth, text: &str| {
+        *program += &format!("{}{}\n", "  ".repeat(depth), text);
+    };
+    for shape in shapes {
+        match shape {
+            Shape::Write(expr) => line(program, depth, &format!("scrie {}", expr)),
+            Shape::Assign(var, expr) => line(program, depth, &format!("{} <- {}", var, expr)),
+            Shape::If(cond, body) => {
+                line(program, depth, &format!("daca {} atunci", cond));
+                render(body, depth + 1, program);
+            }
+            Shape::Loop {
+                kind: LoopKind::For,
+                start,
+                end,
+                step,
+                body,
+            } => {
+                let header = format!(
+                    "pentru i{} <- {}, {}, {} executa",
+                    depth + 1,
+                    start,
+                    end,
+                    step
+                );
+                line(program, depth, &header);
+                render(body, depth + 1, program);
+            }
+            Shape::Loop {
+                kind,
+                start,
+                end,
+                body,
+                ..
+            } => {
+                let counter = format!("w{}", depth + 1);
+                line(program, depth, &format!("{} <- {}", counter, start));
+                match kind {
+                    LoopKind::While => line(
+                        program,
+                        depth,
+                        &format!("cat timp {} <= {} executa", counter, end),
+                    ),
+                    LoopKind::DoWhile => line(program, depth, "executa"),
+                    _ => line(program, depth, "repeta"),
+                }
+                render(body, depth + 1, program);
+                line(
+                    program,
+                    depth + 1,
+                    &format!("{} <- {} + 1", counter, counter),
+                );
+                match kind {
+                    LoopKind::DoWhile => {
+                        line(program, depth, &format!("cat timp {} <= {}", counter, end))
+                    }
+                    LoopKind::Repeat => {
+                        line(program, depth, &format!("pana cand {} > {}", counter, end))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn rewritten_loops_behave_the_same(shapes in shapes(), rewrite in select(&LOOP_REWRITES[..])) {
+        let mut program = "a <- 1\nb <- 2\ni1 <- 0\n".to_string();
+        render(&shapes, 0, &mut program);
+        let expected = run_steps(&program, "", Some(5_000));
+        // Only programs that end are compared, and a `pentru` loop whose step
+        // changes only fails before it is rewritten.
+        prop_assume!(!matches!(
+            expected.1,
+            Some(ExecutionErrorKind::StepLimitExceeded(_) | ExecutionErrorKind::VariableStepInLoop)
+        ));
+
+        let (from, to) = rewrite;
+        let rewritten = rewrite_loops(&program, from, to, &Options::default()).unwrap();
+        prop_assert_eq!(run_steps(&rewritten, "", Some(50_000)), expected, "{}\n{}", program, rewritten);
+    }
+
+    #[test]
+    fn formatting_is_stable(shapes in shapes()) {
+        let mut program = "a<-1\nb<-(2)\ni1<-0\n".to_string();
+        render(&shapes, 0, &mut program);
+        let formatted = format_program(&program, &Options::default()).unwrap();
+        prop_assert!(same_program(&formatted, &program, &Options::default()).unwrap(), "{}", formatted);
+        prop_assert_eq!(
+            format_program(&formatted, &Options::default()).unwrap(),
+            formatted.clone()
+        );
+        prop_assert_eq!(
+            run_steps(&formatted, "", Some(5_000)),
+            run_steps(&program, "", Some(5_000))
+        );
+    }
+}
@@ -0,0 +1,46 @@
+#include <iomanip>
+#include <iostream>
+#include <sstream>
+#include <string>
+using namespace std;
+
+// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == "-0" ? "0" : text;
+}
+
+double suma(double v[], int n);
+double medie(double v[], int n);
+
+double suma(double v[], int n)
+{
+    double s;
+    int i;
+    s = 0;
+    for (i = 1; i <= n; i++) {
+        s = s + v[i];
+    }
+    return s;
+}
+
+double medie(double v[], int n)
+{
+    return suma(v, n) / n;
+}
+
+int main()
+{
+    double v[1001];
+    v[1] = 1.5;
+    v[2] = 2;
+    cout << text(medie(v, 2)) << endl;
+    return 0;
+}
@@ -0,0 +1,48 @@
+#include <iomanip>
+#include <iostream>
+#include <sstream>
+#include <string>
+using namespace std;
+
+// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == "-0" ? "0" : text;
+}
+
+int main()
+{
+    int n, i, j;
+    double v[1001], s, aux, w[1001];
+    cin >> n;
+    for (i = 1; i <= n; i++) {
+        cin >> v[i];
+    }
+    s = 0;
+    for (i = n; i >= 1; i--) {
+        s = s + v[i];
+        cout << text(v[i]) << endl;
+    }
+    cout << "suma " << text(s) << endl;
+    for (i = 1; i <= n - 1; i++) {
+        for (j = i + 1; j <= n; j++) {
+            if (v[i] > v[j]) {
+                aux = v[i];
+                v[i] = v[j];
+                v[j] = aux;
+            }
+        }
+    }
+    for (i = 1; i <= n; i++) {
+        w[n - i] = v[i] * 2;
+    }
+    cout << text(v[1]) << " " << text(v[n]) << " " << text(w[0]) << " " << text(w[n - 1]) << endl;
+    return 0;
+}
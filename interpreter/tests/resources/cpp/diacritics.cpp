@@ -0,0 +1,25 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    double n;
+    int s, i, numar;
+    cin >> n;
+    s = 0;
+    i = 1;
+    while (i <= n) {
+        if (i % 2 != 0 && i >= 3) {
+            s = s + i;
+        }
+        i = i + 1;
+    }
+    do {
+        n = n - 1;
+    } while (!(n == 0));
+    for (numar = 1; numar <= 2; numar++) {
+        cout << numar << endl;
+    }
+    cout << s << endl;
+    return 0;
+}
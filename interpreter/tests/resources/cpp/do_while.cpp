@@ -0,0 +1,12 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int x;
+    x = 0;
+    do {
+        cout << x << endl;
+    } while (x);
+    return 0;
+}
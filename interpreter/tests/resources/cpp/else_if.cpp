@@ -0,0 +1,13 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    if (5 + 5) {
+        cout << 10 << endl;
+        cout << 16 << endl;
+    } else {
+        cout << 1 << endl;
+    }
+    return 0;
+}
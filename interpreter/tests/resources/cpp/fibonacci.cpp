@@ -0,0 +1,24 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int n, x, y, i, tmp;
+    cout << "Introduceti n:" << endl;
+    cin >> n;
+    if (n == 0) {
+        cout << "fib(" << n << ") = " << 0 << endl;
+    } else if (n == 1) {
+        cout << "fib(" << n << ") = " << 1 << endl;
+    } else {
+        x = 0;
+        y = 1;
+        for (i = 0; i <= n - 2; i++) {
+            tmp = x;
+            x = y;
+            y = tmp + x;
+        }
+        cout << "fib(" << n << ") = " << y << endl;
+    }
+    return 0;
+}
@@ -0,0 +1,11 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int var;
+    for (var = 0; var <= 12; var += 2) {
+        cout << var << endl;
+    }
+    return 0;
+}
@@ -0,0 +1,10 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    if (5 + 5) {
+        cout << 10 << endl;
+    }
+    return 0;
+}
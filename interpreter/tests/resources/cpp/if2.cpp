@@ -0,0 +1,13 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    if (1) {
+        if (2) {
+            cout << 5 << endl;
+            cout << 6 << endl;
+        }
+    }
+    return 0;
+}
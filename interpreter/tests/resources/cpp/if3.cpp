@@ -0,0 +1,15 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    if (1) {
+        if (0) {
+            cout << 5 << endl;
+        }
+        cout << 6 << endl;
+    } else {
+        cout << 1 << endl;
+    }
+    return 0;
+}
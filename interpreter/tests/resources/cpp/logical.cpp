@@ -0,0 +1,22 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int x, y;
+    for (x = -2; x <= 12; x += 3) {
+        if (x > 0 && x < 10) {
+            cout << x << " in interval" << endl;
+        } else if (x != 4 || !(x != 4)) {
+            cout << x << " in afara" << endl;
+        }
+    }
+    y = 0;
+    if (y == 0 || 10 / y > 1) {
+        cout << "scurtcircuit" << endl;
+    }
+    if (!(y != 0 && 10 / y > 1)) {
+        cout << "ok" << endl;
+    }
+    return 0;
+}
@@ -0,0 +1,31 @@
+#include <algorithm>
+#include <cmath>
+#include <iomanip>
+#include <iostream>
+#include <sstream>
+#include <string>
+using namespace std;
+
+// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == "-0" ? "0" : text;
+}
+
+int main()
+{
+    double x;
+    x = 7.8;
+    cout << (int)floor(x) << " " << (int)floor(-x) << " " << 17 / 5 << endl;
+    cout << abs(3 - 10) << " " << text(abs(-2.5)) << endl;
+    cout << text(sqrt(16)) << " " << text(sqrt(2)) << endl;
+    cout << max(max(3, 8), 5) << " " << text(min<double>(4, 2.5)) << " " << abs(-6) << endl;
+    return 0;
+}
@@ -0,0 +1,56 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int n, i, j, a[101][101], d1, d2, k, sus, jos, st, dr, b[1001];
+    cin >> n;
+    for (i = 1; i <= n; i++) {
+        for (j = 1; j <= n; j++) {
+            a[i][j] = (i - 1) * n + j;
+        }
+    }
+    for (i = 1; i <= n; i++) {
+        cout << a[i][1] << " " << a[i][2] << " " << a[i][3] << endl;
+    }
+    d1 = 0;
+    d2 = 0;
+    for (i = 1; i <= n; i++) {
+        d1 = d1 + a[i][i];
+        d2 = d2 + a[i][n - i + 1];
+    }
+    cout << d1 << " " << d2 << endl;
+    k = 1;
+    sus = 1;
+    jos = n;
+    st = 1;
+    dr = n;
+    while (sus <= jos && st <= dr) {
+        for (j = st; j <= dr; j++) {
+            b[k] = a[sus][j];
+            k = k + 1;
+        }
+        sus = sus + 1;
+        for (i = sus; i <= jos; i++) {
+            b[k] = a[i][dr];
+            k = k + 1;
+        }
+        dr = dr - 1;
+        if (sus <= jos) {
+            for (j = dr; j >= st; j--) {
+                b[k] = a[jos][j];
+                k = k + 1;
+            }
+            jos = jos - 1;
+        }
+        if (st <= dr) {
+            for (i = jos; i >= sus; i--) {
+                b[k] = a[i][st];
+                k = k + 1;
+            }
+            st = st + 1;
+        }
+    }
+    cout << b[1] << " " << b[2] << " " << b[3] << " " << b[4] << " " << b[5] << " " << b[6] << " " << b[7] << " " << b[8] << " " << b[9] << endl;
+    return 0;
+}
@@ -0,0 +1,45 @@
+#include <cmath>
+#include <iomanip>
+#include <iostream>
+#include <sstream>
+#include <string>
+using namespace std;
+
+// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == "-0" ? "0" : text;
+}
+
+// Imparte fara rest cand ambele numere sunt intregi.
+double cat(double a, double b)
+{
+    if (a == trunc(a) && b == trunc(b)) {
+        return trunc(a / b);
+    }
+    return a / b;
+}
+
+int main()
+{
+    double a, b, s, v[1001];
+    int n, i;
+    cin >> a >> b;
+    cout << text(a) << " " << text(b) << endl;
+    cout << text(cat(a + b, 2)) << endl;
+    cin >> n;
+    s = 0;
+    for (i = 1; i <= n; i++) {
+        cin >> v[i];
+        s = s + v[i];
+    }
+    cout << text(cat(s, n)) << endl;
+    return 0;
+}
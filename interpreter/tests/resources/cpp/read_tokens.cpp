@@ -0,0 +1,30 @@
+#include <iomanip>
+#include <iostream>
+#include <sstream>
+#include <string>
+using namespace std;
+
+// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == "-0" ? "0" : text;
+}
+
+int main()
+{
+    double n, m, a[1001];
+    string nume;
+    cin >> n >> m;
+    cin >> a[1] >> a[2] >> a[3];
+    cin >> nume;
+    cout << text(n + m) << endl;
+    cout << text(a[1] * a[2] * a[3]) << " " << nume << endl;
+    return 0;
+}
@@ -0,0 +1,13 @@
+#include <iostream>
+#include <string>
+using namespace std;
+
+int main()
+{
+    string a, b, c, d;
+    cin >> a >> b;
+    cout << a << b << endl;
+    cin >> a >> c >> d;
+    cout << a << d << endl;
+    return 0;
+}
@@ -0,0 +1,31 @@
+#include <iomanip>
+#include <iostream>
+#include <sstream>
+#include <string>
+using namespace std;
+
+// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == "-0" ? "0" : text;
+}
+
+int main()
+{
+    double a, b, media, x;
+    cin >> a >> b;
+    media = (a + b) / 2.0;
+    cout << text(media) << endl;
+    cout << 7 / 2 << " " << text(7 / 2.0) << endl;
+    cout << text(1 / 3.0) << endl;
+    x = 2.5 * 2;
+    cout << text(x) << " " << text(0.25 - x) << endl;
+    return 0;
+}
@@ -0,0 +1,13 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int x;
+    x = 0;
+    do {
+        cout << x << endl;
+        x = x + 1;
+    } while (!x);
+    return 0;
+}
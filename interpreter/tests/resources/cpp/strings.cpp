@@ -0,0 +1,37 @@
+#include <iostream>
+#include <string>
+using namespace std;
+
+int main()
+{
+    string nume, cuvant, salut, invers;
+    int i, vocale;
+    char c;
+    cin >> nume >> cuvant;
+    salut = "Salut, " + nume;
+    cout << salut << endl;
+    cout << (int)cuvant.length() << endl;
+    invers = "";
+    for (i = (int)cuvant.length() - 1; i >= 0; i--) {
+        invers = invers + cuvant[i];
+    }
+    if (invers == cuvant) {
+        cout << cuvant << " este palindrom" << endl;
+    } else {
+        cout << cuvant << " nu este palindrom" << endl;
+    }
+    vocale = 0;
+    for (i = 0; i <= (int)cuvant.length() - 1; i++) {
+        c = cuvant[i];
+        if (c == 'a' || c == 'e' || c == 'i' || c == 'o' || c == 'u') {
+            vocale = vocale + 1;
+        }
+    }
+    cout << "vocale: " << vocale << endl;
+    if (string("ana") < "andrei" && string(1, 'b') > "abc") {
+        cout << "ordine lexicografica" << endl;
+    }
+    cuvant[0] = 'R';
+    cout << cuvant << endl;
+    return 0;
+}
@@ -0,0 +1,72 @@
+#include <algorithm>
+#include <iomanip>
+#include <iostream>
+#include <sstream>
+#include <string>
+using namespace std;
+
+// Scrie un numar real cu cel mult sase zecimale.
+string text(double valoare)
+{
+    ostringstream out;
+    out << fixed << setprecision(6) << valoare;
+    string text = out.str();
+    text.erase(text.find_last_not_of('0') + 1);
+    if (text[text.length() - 1] == '.') {
+        text.erase(text.length() - 1);
+    }
+    return text == "-0" ? "0" : text;
+}
+
+double factorial(double n);
+double fibonacci(double n);
+int cmmdc(int a, int b);
+void afiseaza(double x, int y);
+
+double factorial(double n)
+{
+    if (n <= 1) {
+        return 1;
+    }
+    return n * factorial(n - 1);
+}
+
+double fibonacci(double n)
+{
+    if (n < 2) {
+        return n;
+    }
+    return fibonacci(n - 1) + fibonacci(n - 2);
+}
+
+int cmmdc(int a, int b)
+{
+    int r;
+    while (b != 0) {
+        r = a % b;
+        a = b;
+        b = r;
+    }
+    return a;
+}
+
+void afiseaza(double x, int y)
+{
+    if (y == 0) {
+        cout << text(x) << endl;
+        return;
+    }
+    cout << text(x) << " " << y << endl;
+}
+
+int main()
+{
+    double n;
+    int a;
+    cin >> n;
+    a = 5;
+    afiseaza(factorial(n), 0);
+    afiseaza(fibonacci(n), cmmdc(48, 18));
+    cout << a << " " << text(max<double>(a, n)) << endl;
+    return 0;
+}
@@ -0,0 +1,16 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int n, i;
+    cin >> n;
+    for (i = 1; i <= n; i++) {
+        if (i % 2 == 0) {
+            cout << i << " par" << endl;
+        } else {
+            cout << i << endl;
+        }
+    }
+    return 0;
+}
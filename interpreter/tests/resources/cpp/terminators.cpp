@@ -0,0 +1,27 @@
+#include <iostream>
+using namespace std;
+
+void afiseaza(int x)
+{
+    cout << "suma este " << x << endl;
+}
+
+int main()
+{
+    int n, suma, i;
+    cin >> n;
+    suma = 0;
+    for (i = 1; i <= n; i++) {
+        if (i % 2 == 0) {
+            suma = suma + i;
+        } else {
+            cout << i << endl;
+        }
+    }
+    while (n > 3) {
+        n = n - 2;
+    }
+    afiseaza(suma);
+    cout << n << endl;
+    return 0;
+}
@@ -0,0 +1,13 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int x;
+    x = 5;
+    while (x > 1) {
+        cout << x << endl;
+        x = x - 1;
+    }
+    return 0;
+}
@@ -0,0 +1,17 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int n, i, v[1001];
+    cin >> n;
+    for (i = 1; i <= n; i++) {
+        v[i] = i * i;
+    }
+    for (i = 1; i <= n; i++) {
+        cout << v[i] << " ";
+    }
+    cout << "\tgata" << endl;
+    cout << "linia 1\nlinia 2" << "\n" << "it's \"ok\"" << endl;
+    return 0;
+}
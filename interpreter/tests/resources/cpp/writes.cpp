@@ -0,0 +1,14 @@
+#include <iostream>
+using namespace std;
+
+int main()
+{
+    int a, b, var;
+    a = 3;
+    b = 5;
+    cout << 1 + 3 << endl;
+    cout << a + b - 3 << endl;
+    var = 1;
+    cout << 3 * 4 + var << endl;
+    return 0;
+}
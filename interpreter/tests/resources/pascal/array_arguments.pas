@@ -0,0 +1,48 @@
+program pseudocod;
+
+type
+  VectorReal = array[0..1000] of real;
+
+var
+  v: VectorReal;
+
+{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+
+function suma(v: VectorReal; n: longint): real;
+var
+  s: real;
+  i: longint;
+begin
+  s := 0;
+  for i := 1 to n do begin
+    s := s + v[i];
+  end;
+  suma := s;
+end;
+
+function medie(v: VectorReal; n: longint): real;
+begin
+  medie := suma(v, n) / n;
+end;
+
+begin
+  v[1] := 1.5;
+  v[2] := 2;
+  writeln(text(medie(v, 2)));
+end.
@@ -0,0 +1,53 @@
+program pseudocod;
+
+type
+  VectorReal = array[0..1000] of real;
+
+var
+  n, i, j: longint;
+  v, w: VectorReal;
+  s, aux: real;
+
+{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+
+begin
+  read(n);
+  for i := 1 to n do begin
+    read(v[i]);
+  end;
+  s := 0;
+  for i := n downto 1 do begin
+    s := s + v[i];
+    writeln(text(v[i]));
+  end;
+  writeln('suma ', text(s));
+  for i := 1 to n - 1 do begin
+    for j := i + 1 to n do begin
+      if v[i] > v[j] then begin
+        aux := v[i];
+        v[i] := v[j];
+        v[j] := aux;
+      end;
+    end;
+  end;
+  for i := 1 to n do begin
+    w[n - i] := v[i] * 2;
+  end;
+  writeln(text(v[1]), ' ', text(v[n]), ' ', text(w[0]), ' ', text(w[n - 1]));
+end.
@@ -0,0 +1,14 @@
+program pseudocod;
+
+var
+  n, s: longint;
+
+begin
+  read(n);
+  s := 0;
+  while n > 0 do begin
+    s := s + n mod 10;
+    n := n div 10;
+  end;
+  writeln('suma este ', s);
+end.
@@ -0,0 +1,24 @@
+program pseudocod;
+
+var
+  n: real;
+  s, i, numar: longint;
+
+begin
+  read(n);
+  s := 0;
+  i := 1;
+  while i <= n do begin
+    if (i mod 2 <> 0) and (i >= 3) then begin
+      s := s + i;
+    end;
+    i := i + 1;
+  end;
+  repeat
+    n := n - 1;
+  until n = 0;
+  for numar := 1 to 2 do begin
+    writeln(numar);
+  end;
+  writeln(s);
+end.
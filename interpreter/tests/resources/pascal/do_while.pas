@@ -0,0 +1,11 @@
+program pseudocod;
+
+var
+  x: longint;
+
+begin
+  x := 0;
+  repeat
+    writeln(x);
+  until x = 0;
+end.
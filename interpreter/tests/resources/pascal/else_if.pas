@@ -0,0 +1,10 @@
+program pseudocod;
+
+begin
+  if 5 + 5 <> 0 then begin
+    writeln(10);
+    writeln(16);
+  end else begin
+    writeln(1);
+  end;
+end.
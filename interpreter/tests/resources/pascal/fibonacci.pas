@@ -0,0 +1,23 @@
+program pseudocod;
+
+var
+  n, x, y, i, tmp: longint;
+
+begin
+  writeln('Introduceti n:');
+  read(n);
+  if n = 0 then begin
+    writeln('fib(', n, ') = ', 0);
+  end else if n = 1 then begin
+    writeln('fib(', n, ') = ', 1);
+  end else begin
+    x := 0;
+    y := 1;
+    for i := 0 to n - 2 do begin
+      tmp := x;
+      x := y;
+      y := tmp + x;
+    end;
+    writeln('fib(', n, ') = ', y);
+  end;
+end.
@@ -0,0 +1,12 @@
+program pseudocod;
+
+var
+  var_: longint;
+
+begin
+  var_ := 0;
+  while var_ <= 12 do begin
+    writeln(var_);
+    var_ := var_ + 2;
+  end;
+end.
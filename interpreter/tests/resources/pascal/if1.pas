@@ -0,0 +1,7 @@
+program pseudocod;
+
+begin
+  if 5 + 5 <> 0 then begin
+    writeln(10);
+  end;
+end.
@@ -0,0 +1,10 @@
+program pseudocod;
+
+begin
+  if 1 <> 0 then begin
+    if 2 <> 0 then begin
+      writeln(5);
+      writeln(6);
+    end;
+  end;
+end.
@@ -0,0 +1,12 @@
+program pseudocod;
+
+begin
+  if 1 <> 0 then begin
+    if 0 <> 0 then begin
+      writeln(5);
+    end;
+    writeln(6);
+  end else begin
+    writeln(1);
+  end;
+end.
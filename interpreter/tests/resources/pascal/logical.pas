@@ -0,0 +1,23 @@
+program pseudocod;
+
+var
+  x, y: longint;
+
+begin
+  x := -2;
+  while x <= 12 do begin
+    if (x > 0) and (x < 10) then begin
+      writeln(x, ' in interval');
+    end else if (x <> 4) or not (x <> 4) then begin
+      writeln(x, ' in afara');
+    end;
+    x := x + 3;
+  end;
+  y := 0;
+  if (y = 0) or (10 div y > 1) then begin
+    writeln('scurtcircuit');
+  end;
+  if not ((y <> 0) and (10 div y > 1)) then begin
+    writeln('ok');
+  end;
+end.
@@ -0,0 +1,32 @@
+program pseudocod;
+
+uses math;
+
+var
+  x: real;
+
+{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+
+begin
+  x := 7.8;
+  writeln(floor(x), ' ', floor(-x), ' ', 17 div 5);
+  writeln(abs(3 - 10), ' ', text(abs(-2.5)));
+  writeln(text(sqrt(16)), ' ', text(sqrt(2)));
+  writeln(max(max(3, 8), 5), ' ', text(min(4, 2.5)), ' ', abs(-6));
+end.
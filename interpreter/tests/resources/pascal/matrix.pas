@@ -0,0 +1,61 @@
+program pseudocod;
+
+type
+  Matrice = array[0..100, 0..100] of longint;
+  Vector = array[0..1000] of longint;
+
+var
+  n, i, j, d1, d2, k, sus, jos, st, dr: longint;
+  a: Matrice;
+  b: Vector;
+
+begin
+  read(n);
+  for i := 1 to n do begin
+    for j := 1 to n do begin
+      a[i, j] := (i - 1) * n + j;
+    end;
+  end;
+  for i := 1 to n do begin
+    writeln(a[i, 1], ' ', a[i, 2], ' ', a[i, 3]);
+  end;
+  d1 := 0;
+  d2 := 0;
+  for i := 1 to n do begin
+    d1 := d1 + a[i, i];
+    d2 := d2 + a[i, n - i + 1];
+  end;
+  writeln(d1, ' ', d2);
+  k := 1;
+  sus := 1;
+  jos := n;
+  st := 1;
+  dr := n;
+  while (sus <= jos) and (st <= dr) do begin
+    for j := st to dr do begin
+      b[k] := a[sus, j];
+      k := k + 1;
+    end;
+    sus := sus + 1;
+    for i := sus to jos do begin
+      b[k] := a[i, dr];
+      k := k + 1;
+    end;
+    dr := dr - 1;
+    if sus <= jos then begin
+      for j := dr downto st do begin
+        b[k] := a[jos, j];
+        k := k + 1;
+      end;
+      jos := jos - 1;
+    end;
+    if st <= dr then begin
+      for i := jos downto sus do begin
+        b[k] := a[i, st];
+        k := k + 1;
+      end;
+      st := st + 1;
+    end;
+  end;
+  writeln(b[1], ' ', b[2], ' ', b[3], ' ', b[4], ' ', b[5], ' ', b[6], ' ', b[7], ' ', b[8], ' ', b[9]);
+end.
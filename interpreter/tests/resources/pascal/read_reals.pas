@@ -0,0 +1,50 @@
+program pseudocod;
+
+type
+  VectorReal = array[0..1000] of real;
+
+var
+  a, b, s: real;
+  n, i: longint;
+  v: VectorReal;
+
+{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+
+{ Imparte fara rest cand ambele numere sunt intregi. }
+function cat(a, b: real): real;
+begin
+  if (frac(a) = 0) and (frac(b) = 0) then begin
+    cat := trunc(a / b);
+  end else begin
+    cat := a / b;
+  end;
+end;
+
+begin
+  read(a, b);
+  writeln(text(a), ' ', text(b));
+  writeln(text(cat(a + b, 2)));
+  read(n);
+  s := 0;
+  for i := 1 to n do begin
+    read(v[i]);
+    s := s + v[i];
+  end;
+  writeln(text(cat(s, n)));
+end.
@@ -0,0 +1,53 @@
+program pseudocod;
+
+type
+  VectorReal = array[0..1000] of real;
+
+var
+  n, m: real;
+  a: VectorReal;
+  nume: string;
+
+{ Citeste urmatorul cuvant, sarind peste spatii si randuri noi. }
+procedure citeste(var cuvant: string);
+var
+  c: char;
+begin
+  cuvant := '';
+  repeat
+    read(c);
+  until eof or (c > ' ');
+  while c > ' ' do begin
+    cuvant := cuvant + c;
+    if eof then begin
+      exit;
+    end;
+    read(c);
+  end;
+end;
+
+{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+
+begin
+  read(n, m);
+  read(a[1], a[2], a[3]);
+  citeste(nume);
+  writeln(text(n + m));
+  writeln(text(a[1] * a[2] * a[3]), ' ', nume);
+end.
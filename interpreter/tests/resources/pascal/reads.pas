@@ -0,0 +1,32 @@
+program pseudocod;
+
+var
+  a, b, c, d: string;
+
+{ Citeste urmatorul cuvant, sarind peste spatii si randuri noi. }
+procedure citeste(var cuvant: string);
+var
+  c: char;
+begin
+  cuvant := '';
+  repeat
+    read(c);
+  until eof or (c > ' ');
+  while c > ' ' do begin
+    cuvant := cuvant + c;
+    if eof then begin
+      exit;
+    end;
+    read(c);
+  end;
+end;
+
+begin
+  citeste(a);
+  citeste(b);
+  writeln(a, b);
+  citeste(a);
+  citeste(c);
+  citeste(d);
+  writeln(a, d);
+end.
@@ -0,0 +1,32 @@
+program pseudocod;
+
+var
+  a, b, media, x: real;
+
+{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+
+begin
+  read(a, b);
+  media := (a + b) / 2.0;
+  writeln(text(media));
+  writeln(7 div 2, ' ', text(7 / 2.0));
+  writeln(text(1 / 3.0));
+  x := 2.5 * 2;
+  writeln(text(x), ' ', text(0.25 - x));
+end.
@@ -0,0 +1,12 @@
+program pseudocod;
+
+var
+  x: longint;
+
+begin
+  x := 0;
+  repeat
+    writeln(x);
+    x := x + 1;
+  until x <> 0;
+end.
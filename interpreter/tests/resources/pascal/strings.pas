@@ -0,0 +1,54 @@
+program pseudocod;
+
+var
+  nume, cuvant, salut, invers: string;
+  i, vocale: longint;
+  c: char;
+
+{ Citeste urmatorul cuvant, sarind peste spatii si randuri noi. }
+procedure citeste(var cuvant: string);
+var
+  c: char;
+begin
+  cuvant := '';
+  repeat
+    read(c);
+  until eof or (c > ' ');
+  while c > ' ' do begin
+    cuvant := cuvant + c;
+    if eof then begin
+      exit;
+    end;
+    read(c);
+  end;
+end;
+
+begin
+  citeste(nume);
+  citeste(cuvant);
+  salut := 'Salut, ' + nume;
+  writeln(salut);
+  writeln(length(cuvant));
+  invers := '';
+  for i := length(cuvant) - 1 downto 0 do begin
+    invers := invers + cuvant[i + 1];
+  end;
+  if invers = cuvant then begin
+    writeln(cuvant, ' este palindrom');
+  end else begin
+    writeln(cuvant, ' nu este palindrom');
+  end;
+  vocale := 0;
+  for i := 0 to length(cuvant) - 1 do begin
+    c := cuvant[i + 1];
+    if (c = 'a') or (c = 'e') or (c = 'i') or (c = 'o') or (c = 'u') then begin
+      vocale := vocale + 1;
+    end;
+  end;
+  writeln('vocale: ', vocale);
+  if ('ana' < 'andrei') and ('b' > 'abc') then begin
+    writeln('ordine lexicografica');
+  end;
+  cuvant[1] := 'R';
+  writeln(cuvant);
+end.
@@ -0,0 +1,72 @@
+program pseudocod;
+
+uses math;
+
+var
+  n: real;
+  a: longint;
+
+{ Scrie un numar real cu cel mult sase zecimale. }
+function text(valoare: real): string;
+var
+  s: string;
+begin
+  str(valoare:0:6, s);
+  while s[length(s)] = '0' do begin
+    delete(s, length(s), 1);
+  end;
+  if s[length(s)] = '.' then begin
+    delete(s, length(s), 1);
+  end;
+  if s = '-0' then begin
+    s := '0';
+  end;
+  text := s;
+end;
+
+function factorial(n: real): real;
+begin
+  if n <= 1 then begin
+    factorial := 1;
+    exit;
+  end;
+  factorial := n * factorial(n - 1);
+end;
+
+function fibonacci(n: real): real;
+begin
+  if n < 2 then begin
+    fibonacci := n;
+    exit;
+  end;
+  fibonacci := fibonacci(n - 1) + fibonacci(n - 2);
+end;
+
+function cmmdc(a, b: longint): longint;
+var
+  r: longint;
+begin
+  while b <> 0 do begin
+    r := a mod b;
+    a := b;
+    b := r;
+  end;
+  cmmdc := a;
+end;
+
+procedure afiseaza(x: real; y: longint);
+begin
+  if y = 0 then begin
+    writeln(text(x));
+    exit;
+  end;
+  writeln(text(x), ' ', y);
+end;
+
+begin
+  read(n);
+  a := 5;
+  afiseaza(factorial(n), 0);
+  afiseaza(fibonacci(n), cmmdc(48, 18));
+  writeln(a, ' ', text(max(a, n)));
+end.
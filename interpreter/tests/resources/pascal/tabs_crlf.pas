@@ -0,0 +1,15 @@
+program pseudocod;
+
+var
+  n, i: longint;
+
+begin
+  read(n);
+  for i := 1 to n do begin
+    if i mod 2 = 0 then begin
+      writeln(i, ' par');
+    end else begin
+      writeln(i);
+    end;
+  end;
+end.
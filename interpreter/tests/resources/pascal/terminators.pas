@@ -0,0 +1,26 @@
+program pseudocod;
+
+var
+  n, suma, i: longint;
+
+procedure afiseaza(x: longint);
+begin
+  writeln('suma este ', x);
+end;
+
+begin
+  read(n);
+  suma := 0;
+  for i := 1 to n do begin
+    if i mod 2 = 0 then begin
+      suma := suma + i;
+    end else begin
+      writeln(i);
+    end;
+  end;
+  while n > 3 do begin
+    n := n - 2;
+  end;
+  afiseaza(suma);
+  writeln(n);
+end.
@@ -0,0 +1,12 @@
+program pseudocod;
+
+var
+  x: longint;
+
+begin
+  x := 5;
+  while x > 1 do begin
+    writeln(x);
+    x := x - 1;
+  end;
+end.
@@ -0,0 +1,20 @@
+program pseudocod;
+
+type
+  Vector = array[0..1000] of longint;
+
+var
+  n, i: longint;
+  v: Vector;
+
+begin
+  read(n);
+  for i := 1 to n do begin
+    v[i] := i * i;
+  end;
+  for i := 1 to n do begin
+    write(v[i], ' ');
+  end;
+  writeln(#9'gata');
+  writeln('linia 1'#10'linia 2', #10, 'it''s "ok"');
+end.
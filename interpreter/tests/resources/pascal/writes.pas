@@ -0,0 +1,13 @@
+program pseudocod;
+
+var
+  a, b, var_: longint;
+
+begin
+  a := 3;
+  b := 5;
+  writeln(1 + 3);
+  writeln(a + b - 3);
+  var_ := 1;
+  writeln(3 * 4 + var_);
+end.
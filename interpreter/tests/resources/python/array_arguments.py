@@ -0,0 +1,21 @@
+def text(valoare):
+    """Scrie un numar real cu cel mult sase zecimale."""
+    valoare = f"{valoare:.6f}".rstrip("0").rstrip(".")
+    return "0" if valoare == "-0" else valoare
+
+
+def suma(v, n):
+    s = 0
+    for i in range(1, n + 1):
+        s = s + v[i]
+    return s
+
+
+def medie(v, n):
+    return suma(v, n) / n
+
+
+v = {}
+v[1] = 1.5
+v[2] = 2
+print(text(medie(v, 2)))
@@ -0,0 +1,43 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+v = {}
+w = {}
+n = citeste()
+for i in range(1, n + 1):
+    v[i] = citeste()
+s = 0
+for i in range(n, 0, -1):
+    s = s + v[i]
+    print(v[i])
+print("suma ", s, sep="")
+for i in range(1, n):
+    for j in range(i + 1, n + 1):
+        if v[i] > v[j]:
+            aux = v[i]
+            v[i] = v[j]
+            v[j] = aux
+for i in range(1, n + 1):
+    w[n - i] = v[i] * 2
+print(v[1], " ", v[n], " ", w[0], " ", w[n - 1], sep="")
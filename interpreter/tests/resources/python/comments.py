@@ -0,0 +1,44 @@
+import math
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+def cat(a, b):
+    """Imparte doi intregi fara rest, rotunjind spre zero."""
+    if isinstance(a, int) and isinstance(b, int):
+        q = abs(a) // abs(b)
+        return q if (a < 0) == (b < 0) else -q
+    return a / b
+
+
+def rest(a, b):
+    """Restul impartirii a doi intregi, cu semnul deimpartitului."""
+    return a - b * cat(a, b)
+
+
+n = citeste()
+s = 0
+while n > 0:
+    s = s + rest(n, 10)
+    n = math.floor(cat(n, 10))
+print("suma este ", s, sep="")
@@ -0,0 +1,5 @@
+x = 0
+while True:
+    print(x)
+    if not x:
+        break
@@ -0,0 +1,5 @@
+if 5 + 5:
+    print(10)
+    print(16)
+else:
+    print(1)
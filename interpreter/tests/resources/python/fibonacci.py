@@ -0,0 +1,38 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+print("Introduceti n:")
+n = citeste()
+if n == 0:
+    print("fib(", n, ") = ", 0, sep="")
+elif n == 1:
+    print("fib(", n, ") = ", 1, sep="")
+else:
+    x = 0
+    y = 1
+    for i in range(0, n - 2 + 1):
+        tmp = x
+        x = y
+        y = tmp + x
+    print("fib(", n, ") = ", y, sep="")
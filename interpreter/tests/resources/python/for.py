@@ -0,0 +1,2 @@
+for var in range(0, 13, 2):
+    print(var)
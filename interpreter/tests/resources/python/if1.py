@@ -0,0 +1,2 @@
+if 5 + 5:
+    print(10)
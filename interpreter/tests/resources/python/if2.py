@@ -0,0 +1,4 @@
+if 1:
+    if 2:
+        print(5)
+        print(6)
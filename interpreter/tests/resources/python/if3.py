@@ -0,0 +1,6 @@
+if 1:
+    if 0:
+        print(5)
+    print(6)
+else:
+    print(1)
@@ -0,0 +1,18 @@
+def cat(a, b):
+    """Imparte doi intregi fara rest, rotunjind spre zero."""
+    if isinstance(a, int) and isinstance(b, int):
+        q = abs(a) // abs(b)
+        return q if (a < 0) == (b < 0) else -q
+    return a / b
+
+
+for x in range(-2, 13, 3):
+    if x > 0 and x < 10:
+        print(x, " in interval", sep="")
+    elif x != 4 or not x != 4:
+        print(x, " in afara", sep="")
+y = 0
+if y == 0 or cat(10, y) > 1:
+    print("scurtcircuit")
+if not (y != 0 and cat(10, y) > 1):
+    print("ok")
@@ -0,0 +1,22 @@
+import math
+
+
+def text(valoare):
+    """Scrie un numar real cu cel mult sase zecimale."""
+    valoare = f"{valoare:.6f}".rstrip("0").rstrip(".")
+    return "0" if valoare == "-0" else valoare
+
+
+def cat(a, b):
+    """Imparte doi intregi fara rest, rotunjind spre zero."""
+    if isinstance(a, int) and isinstance(b, int):
+        q = abs(a) // abs(b)
+        return q if (a < 0) == (b < 0) else -q
+    return a / b
+
+
+x = 7.8
+print(math.floor(x), " ", math.floor(-x), " ", math.floor(cat(17, 5)), sep="")
+print(abs(3 - 10), " ", text(abs(-2.5)), sep="")
+print(text(math.sqrt(16)), " ", text(math.sqrt(2)), sep="")
+print(max(3, 8, 5), " ", text(min(4, 2.5)), " ", abs(-6), sep="")
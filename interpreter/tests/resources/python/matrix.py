@@ -0,0 +1,63 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+a = {}
+b = {}
+n = citeste()
+for i in range(1, n + 1):
+    for j in range(1, n + 1):
+        a[i, j] = (i - 1) * n + j
+for i in range(1, n + 1):
+    print(a[i, 1], " ", a[i, 2], " ", a[i, 3], sep="")
+d1 = 0
+d2 = 0
+for i in range(1, n + 1):
+    d1 = d1 + a[i, i]
+    d2 = d2 + a[i, n - i + 1]
+print(d1, " ", d2, sep="")
+k = 1
+sus = 1
+jos = n
+st = 1
+dr = n
+while sus <= jos and st <= dr:
+    for j in range(st, dr + 1):
+        b[k] = a[sus, j]
+        k = k + 1
+    sus = sus + 1
+    for i in range(sus, jos + 1):
+        b[k] = a[i, dr]
+        k = k + 1
+    dr = dr - 1
+    if sus <= jos:
+        for j in range(dr, st - 1, -1):
+            b[k] = a[jos, j]
+            k = k + 1
+        jos = jos - 1
+    if st <= dr:
+        for i in range(jos, sus - 1, -1):
+            b[k] = a[i, st]
+            k = k + 1
+        st = st + 1
+print(b[1], " ", b[2], " ", b[3], " ", b[4], " ", b[5], " ", b[6], " ", b[7], " ", b[8], " ", b[9], sep="")
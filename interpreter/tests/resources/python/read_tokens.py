@@ -0,0 +1,33 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+a = {}
+n = citeste()
+m = citeste()
+a[1] = citeste()
+a[2] = citeste()
+a[3] = citeste()
+nume = citeste()
+print(n + m)
+print(a[1] * a[2] * a[3], " ", nume, sep="")
@@ -0,0 +1,46 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+def text(valoare):
+    """Scrie un numar real cu cel mult sase zecimale."""
+    valoare = f"{valoare:.6f}".rstrip("0").rstrip(".")
+    return "0" if valoare == "-0" else valoare
+
+
+def cat(a, b):
+    """Imparte doi intregi fara rest, rotunjind spre zero."""
+    if isinstance(a, int) and isinstance(b, int):
+        q = abs(a) // abs(b)
+        return q if (a < 0) == (b < 0) else -q
+    return a / b
+
+
+a = citeste()
+b = citeste()
+media = (a + b) / 2.0
+print(text(media))
+print(cat(7, 2), " ", text(7 / 2.0), sep="")
+print(text(1 / 3.0))
+x = 2.5 * 2
+print(text(x), " ", text(0.25 - x), sep="")
@@ -0,0 +1,6 @@
+x = 0
+while True:
+    print(x)
+    x = x + 1
+    if x:
+        break
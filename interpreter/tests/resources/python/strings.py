@@ -0,0 +1,46 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+nume = citeste()
+cuvant = citeste()
+salut = "Salut, " + nume
+print(salut)
+print(len(cuvant))
+invers = ""
+for i in range(len(cuvant) - 1, -1, -1):
+    invers = invers + cuvant[i]
+if invers == cuvant:
+    print(cuvant, " este palindrom", sep="")
+else:
+    print(cuvant, " nu este palindrom", sep="")
+vocale = 0
+for i in range(0, len(cuvant)):
+    c = cuvant[i]
+    if c == "a" or c == "e" or c == "i" or c == "o" or c == "u":
+        vocale = vocale + 1
+print("vocale: ", vocale, sep="")
+if "ana" < "andrei" and "b" > "abc":
+    print("ordine lexicografica")
+cuvant = cuvant[:0] + "R" + cuvant[1:]
+print(cuvant)
@@ -0,0 +1,69 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+def cat(a, b):
+    """Imparte doi intregi fara rest, rotunjind spre zero."""
+    if isinstance(a, int) and isinstance(b, int):
+        q = abs(a) // abs(b)
+        return q if (a < 0) == (b < 0) else -q
+    return a / b
+
+
+def rest(a, b):
+    """Restul impartirii a doi intregi, cu semnul deimpartitului."""
+    return a - b * cat(a, b)
+
+
+def factorial(n):
+    if n <= 1:
+        return 1
+    return n * factorial(n - 1)
+
+
+def fibonacci(n):
+    if n < 2:
+        return n
+    return fibonacci(n - 1) + fibonacci(n - 2)
+
+
+def cmmdc(a, b):
+    while b != 0:
+        r = rest(a, b)
+        a = b
+        b = r
+    return a
+
+
+def afiseaza(x, y):
+    if y == 0:
+        print(x)
+        return
+    print(x, " ", y, sep="")
+
+
+n = citeste()
+a = 5
+afiseaza(factorial(n), 0)
+afiseaza(fibonacci(n), cmmdc(48, 18))
+print(a, " ", max(a, n), sep="")
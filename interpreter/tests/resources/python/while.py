@@ -0,0 +1,4 @@
+x = 5
+while x > 1:
+    print(x)
+    x = x - 1
@@ -0,0 +1,32 @@
+import sys
+
+
+def _cuvinte():
+    for linie in sys.stdin:
+        yield from linie.split()
+
+
+_intrare = _cuvinte()
+
+
+def citeste():
+    """Citeste urmatorul cuvant: un numar, daca arata ca unul, altfel un text."""
+    cuvant = next(_intrare)
+    try:
+        return int(cuvant)
+    except ValueError:
+        pass
+    try:
+        return float(cuvant)
+    except ValueError:
+        return cuvant
+
+
+v = {}
+n = citeste()
+for i in range(1, n + 1):
+    v[i] = i * i
+for i in range(1, n + 1):
+    print(v[i], " ", sep="", end="")
+print("\tgata")
+print("linia 1\nlinia 2", "\n", "it's \"ok\"", sep="")
@@ -0,0 +1,6 @@
+a = 3
+b = 5
+print(1 + 3)
+print(a + b - 3)
+var = 1
+print(3 * 4 + var)